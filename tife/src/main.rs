@@ -5,15 +5,27 @@ pub mod state;
 
 use screen::test::TestScreen;
 use state::State;
-use wcore::{App, AppConfig, graphics::{context::GraphicsContext, screen::Screen}};
 
-fn main() {
+use std::path::Path;
+use color_eyre::Result;
+use wcore::{App, AppConfig, graphics::{context::GraphicsContext, screen::Screen, overlay::DebugOverlay}};
+
+const SETTINGS_PATH: &str = "settings.toml";
+
+fn main() -> Result<()> {
     let app = App::default();
 
-    let config = AppConfig {
-        title : String::from("tife"),
-        
-        .. Default::default()
+    let config = if Path::new(SETTINGS_PATH).exists() {
+        AppConfig::load(SETTINGS_PATH)?
+    } else {
+        let config = AppConfig {
+            title : String::from("tife"),
+            
+            .. Default::default()
+        };
+
+        config.save(SETTINGS_PATH)?;
+        config
     };
 
     let state_lambda = |graphics: &mut GraphicsContext| {
@@ -22,7 +34,8 @@ fn main() {
 
     let screens_lambda = |graphics: &mut GraphicsContext, screens: &mut Vec<Box<dyn Screen<State>>>| {
         screens.push(Box::new(TestScreen::new(graphics)));
+        screens.push(Box::new(DebugOverlay::new(graphics)));
     };
 
-    app.run(config, state_lambda, screens_lambda);
+    return app.run(config, state_lambda, screens_lambda);
 }
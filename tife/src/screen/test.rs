@@ -1,17 +1,16 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc, time::{SystemTime, UNIX_EPOCH}};
 
-use wcore::{graphics::{screen::Screen, context::GraphicsContext, utils, pipeline::{model::ModelPipeline, Pipeline, shader::scene::SceneSlot}, scene::Scene2D, texture::Texture, bindable::Bindable, primitive::mesh::{instanced::InstancedMesh, data::{model::{Model, ModelRaw}, vertex::Vertex}}, drawable::Drawable, camera::Projection}, audio::Audio, clock::SyncClock};
-use cgmath::{vec3, Quaternion, Zero, vec4};
+use wcore::{graphics::{screen::Screen, context::GraphicsContext, utils, pass::RenderPassBuilder, pipeline::shader::scene::SceneSlot, scene::Scene2D, texture::Texture, sprite::SpriteBatch, primitive::rect::Rect, camera::Projection}, audio::Audio, clock::SyncClock, input::{Input, VirtualKeyCode, ElementState}};
+use cgmath::{vec2, vec4};
 
 use crate::state::State;
 
 pub struct TestScreen {
     pub scene: Scene2D,
 
-    pub texture_test: Texture,
+    pub texture_test: Arc<Texture>,
 
-    pub pipeline_model: ModelPipeline,
-    pub mesh_model: InstancedMesh<Model, Vertex>,
+    pub sprites: SpriteBatch,
 
     audio: Audio,
     clock: SyncClock,
@@ -19,52 +18,73 @@ pub struct TestScreen {
 
 impl TestScreen {
     pub fn new(graphics: &GraphicsContext) -> Self {
-        let width = graphics.surface_configuration.width;
-        let height = graphics.surface_configuration.height;
-        let scene = Scene2D::new(&graphics.device, width, height);
-
-        let pipeline_model = ModelPipeline::new(&graphics.device, &graphics.surface_configuration, &scene, false);
-        let mesh_model = InstancedMesh::new(&graphics.device, Vertex::vertices_rect(-0.5, 0.5), vec![]);
-
-        let path = PathBuf::from("resources/textures");
-        let texture_test = Texture::from_path(&graphics.device, &graphics.queue, path.join("test.png"), wgpu::FilterMode::Linear, "test").unwrap();
-
-        pipeline_model.update(&graphics.queue, &scene);
+        let (scene, texture_test, sprites) = Self::resources(graphics);
         
         return Self {
             scene,
 
             texture_test,
 
-            pipeline_model,
-            mesh_model,
+            sprites,
             
             audio: Audio::new().unwrap(),
             clock: SyncClock::new(),
         };
     }
+
+    fn resources(graphics: &GraphicsContext) -> (Scene2D, Arc<Texture>, SpriteBatch) {
+        let width = graphics.surface_configuration.width;
+        let height = graphics.surface_configuration.height;
+        let scene = Scene2D::new(&graphics.device, width, height, graphics.scale_factor);
+
+        let sprites = SpriteBatch::new(graphics, &scene);
+
+        let path = PathBuf::from("resources/textures");
+        let texture_test = Arc::new(Texture::from_path(graphics, path.join("test.png"), wgpu::FilterMode::Linear, "test").unwrap());
+
+        return (scene, texture_test, sprites);
+    }
 }
 
 impl Screen<State> for TestScreen {
     fn render(&mut self, state: &mut State, view: &wgpu::TextureView, graphics: &mut GraphicsContext) {
+        for x in [150.0, 350.0, 550.0] {
+            self.sprites.draw(&self.texture_test, Rect::new(x, 150.0, 100.0, 100.0), Rect::UNIT, vec4(1.0, 1.0, 1.0, 1.0), 0.0, vec2(50.0, 50.0));
+        }
+
+        let target = graphics.attachment(view);
         utils::submit(&graphics.queue, &graphics.device, |encoder| {
-            utils::render(encoder, &view, None, |mut render_pass| {
-                self.mesh_model.instances = vec![
-                    Model { position: vec3(200.0, 200.0, 0.0), rotation: Quaternion::zero(), scale: vec3(100.0, 100.0, 1.0), color: vec4(1.0, 1.0, 1.0, 1.0) },
-                    Model { position: vec3(400.0, 200.0, 0.0), rotation: Quaternion::zero(), scale: vec3(100.0, 100.0, 1.0), color: vec4(1.0, 1.0, 1.0, 1.0) },
-                    Model { position: vec3(600.0, 200.0, 0.0), rotation: Quaternion::zero(), scale: vec3(100.0, 100.0, 1.0), color: vec4(1.0, 1.0, 1.0, 1.0) }
-                ]; self.mesh_model.bake_instances(&graphics.device);
-
-                self.texture_test.bind(&mut render_pass, 1);
-                
-                self.pipeline_model.attach(&mut render_pass);
-                self.mesh_model.draw(&mut render_pass); // Only .draw() is affected by current pipeline
-            });
+            let mut render_pass = RenderPassBuilder::new(target).label("Test Screen").begin(encoder);
+            self.sprites.flush(&graphics.device, &graphics.queue, &mut render_pass);
         });
     }
 
+    fn input(&mut self, state: &mut State, input: &Input, graphics: &mut GraphicsContext) {
+        match input {
+            Input::KeyboardInput { key: Some(VirtualKeyCode::F11), state: ElementState::Pressed, .. } => {
+                graphics.app.toggle_fullscreen();
+            }
+
+            Input::KeyboardInput { key: Some(VirtualKeyCode::F12), state: ElementState::Pressed, .. } => {
+                let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                graphics.request_screenshot(PathBuf::from("screenshots").join(format!("{timestamp}.png")));
+            }
+
+            _ => {}
+        }
+    }
+
     fn resize(&mut self, state: &mut State, width: u32, height: u32, graphics: &mut GraphicsContext) {
         self.scene.projection.resize(width, height);
-        self.pipeline_model.update(&graphics.queue, &self.scene);
+        self.sprites.update(&graphics.queue, &self.scene);
+    }
+
+    fn recreate(&mut self, state: &mut State, graphics: &mut GraphicsContext) {
+        (self.scene, self.texture_test, self.sprites) = Self::resources(graphics);
+    }
+
+    fn rescale(&mut self, state: &mut State, scale_factor: f64, graphics: &mut GraphicsContext) {
+        self.scene.projection.rescale(scale_factor);
+        self.sprites.update(&graphics.queue, &self.scene);
     }
 }
\ No newline at end of file
@@ -0,0 +1,145 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Fields, Ident, LitInt, Result, Error};
+
+/// Implements `VertexLayout`, `Pod` and `Zeroable` for a `#[repr(C)]` struct of vertex fields.
+///
+/// Shader locations count up from 0 in field order, fields spanning several attributes like matrices take one
+/// location per column. `#[location(n)]` on the struct sets the first location, on a field it moves that field
+/// and the ones after it. `#[step(instance)]` on the struct makes it a per-instance buffer.
+#[proc_macro_derive(VertexLayout, attributes(location, step))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    return match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e)     => e.to_compile_error().into(),
+    };
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "VertexLayout can not be derived for generic structs"));
+    }
+
+    if !is_repr_c(&input.attrs)? {
+        return Err(Error::new_spanned(name, "VertexLayout requires #[repr(C)] to have a predictable layout"));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(name, "VertexLayout requires a struct with named fields")),
+        },
+        _ => return Err(Error::new_spanned(name, "VertexLayout can only be derived for structs")),
+    };
+
+    let start = location(&input.attrs)?.unwrap_or(0);
+    let step = step_mode(&input.attrs)?;
+
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let mut placements = vec![];
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+
+        let set_location = location(&field.attrs)?.map(|location| quote! { location = #location; });
+        placements.push(quote! {
+            #set_location
+            let formats = <#ty as __derive::VertexField>::FORMATS;
+            let mut offset = ::core::mem::offset_of!(#name, #ident) as u64;
+            let mut i = 0;
+            while i < formats.len() {
+                attributes[index] = __derive::wgpu::VertexAttribute { format: formats[i], offset, shader_location: location };
+                offset += formats[i].size();
+                location += 1;
+                index += 1;
+                i += 1;
+            }
+        });
+    }
+
+    let padding_error = format!("{} has padding, reorder its fields or add explicit padding fields", name);
+    return Ok(quote! {
+        const _: () = {
+            use ::wcore::__derive;
+
+            // Every field is plain data and nothing sits between them, so the whole struct is plain data too
+            const _: () = assert!(::core::mem::size_of::<#name>() == 0 #(+ ::core::mem::size_of::<#types>())*, #padding_error);
+
+            unsafe impl __derive::bytemuck::Zeroable for #name {}
+            unsafe impl __derive::bytemuck::Pod for #name {}
+
+            impl __derive::VertexLayout for #name {
+                const STEP_MODE: __derive::wgpu::VertexStepMode = #step;
+                const ATTRIBUTES: &'static [__derive::wgpu::VertexAttribute] = &{
+                    const COUNT: usize = 0 #(+ <#types as __derive::VertexField>::FORMATS.len())*;
+
+                    let mut attributes = [__derive::wgpu::VertexAttribute { format: __derive::wgpu::VertexFormat::Float32, offset: 0, shader_location: 0 }; COUNT];
+                    let mut index = 0;
+                    let mut location: u32 = #start;
+                    #(#placements)*
+
+                    let _ = (index, location);
+                    attributes
+                };
+            }
+        };
+    });
+}
+
+fn is_repr_c(attrs: &[Attribute]) -> Result<bool> {
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("repr")) {
+        let mut repr_c = false;
+        attr.parse_args_with(|input: syn::parse::ParseStream| {
+            while !input.is_empty() {
+                let ident = input.parse::<Ident>()?;
+                repr_c |= ident == "C" || ident == "transparent";
+
+                // Skip arguments like align(16) and the separating comma
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    content.parse::<TokenStream2>()?;
+                }
+
+                if !input.is_empty() {
+                    input.parse::<syn::Token![,]>()?;
+                }
+            }
+
+            return Ok(());
+        })?;
+
+        if repr_c {
+            return Ok(true);
+        }
+    }
+
+    return Ok(false);
+}
+
+fn location(attrs: &[Attribute]) -> Result<Option<u32>> {
+    return match attrs.iter().find(|attr| attr.path.is_ident("location")) {
+        Some(attr) => Ok(Some(attr.parse_args::<LitInt>()?.base10_parse()?)),
+        None       => Ok(None),
+    };
+}
+
+fn step_mode(attrs: &[Attribute]) -> Result<TokenStream2> {
+    let Some(attr) = attrs.iter().find(|attr| attr.path.is_ident("step")) else {
+        return Ok(quote! { __derive::wgpu::VertexStepMode::Vertex });
+    };
+
+    let mode = attr.parse_args::<Ident>()?;
+    let variant = match mode.to_string().as_str() {
+        "vertex"   => Ident::new("Vertex", Span::call_site()),
+        "instance" => Ident::new("Instance", Span::call_site()),
+        _ => return Err(Error::new_spanned(mode, "expected #[step(vertex)] or #[step(instance)]")),
+    };
+
+    return Ok(quote! { __derive::wgpu::VertexStepMode::#variant });
+}
\ No newline at end of file
@@ -0,0 +1,137 @@
+use std::{path::Path, fs};
+
+use color_eyre::Result;
+use serde::{Serialize, Deserialize};
+use winit::monitor::MonitorHandle;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub width  : u32,
+    pub height : u32,
+    pub title  : String,
+
+    pub present_mode     : PresentMode,
+    pub fullscreen       : Fullscreen,
+    pub resizable        : bool,
+    pub min_size         : Option<(u32, u32)>,
+    pub samples          : u32,
+    pub frame_cap        : Option<u32>,
+    pub backend          : Backend,
+    pub power_preference : PowerPreference,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        return Self {
+            width  : 1200,
+            height : 800,
+            title  : String::from("App"),
+
+            present_mode     : PresentMode::AutoVsync,
+            fullscreen       : Fullscreen::Windowed,
+            resizable        : true,
+            min_size         : None,
+            samples          : 1,
+            frame_cap        : None,
+            backend          : Backend::All,
+            power_preference : PowerPreference::HighPerformance,
+        };
+    }
+}
+
+impl AppConfig {
+    /// Loads a config from a TOML file, missing fields are set to their defaults.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        return Ok(toml::from_str(&text)?);
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let text = toml::to_string_pretty(self)?;
+        fs::write(path, text)?;
+        return Ok(());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PresentMode {
+    AutoVsync,
+    AutoNoVsync,
+    Fifo,
+    Immediate,
+    Mailbox,
+}
+
+impl From<PresentMode> for wgpu::PresentMode {
+    fn from(value: PresentMode) -> Self {
+        return match value {
+            PresentMode::AutoVsync   => wgpu::PresentMode::AutoVsync,
+            PresentMode::AutoNoVsync => wgpu::PresentMode::AutoNoVsync,
+            PresentMode::Fifo        => wgpu::PresentMode::Fifo,
+            PresentMode::Immediate   => wgpu::PresentMode::Immediate,
+            PresentMode::Mailbox     => wgpu::PresentMode::Mailbox,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Fullscreen {
+    Windowed,
+    Borderless,
+    Exclusive,
+}
+
+impl Fullscreen {
+    pub fn to_winit(self, monitor: Option<MonitorHandle>) -> Option<winit::window::Fullscreen> {
+        return match self {
+            Fullscreen::Windowed   => None,
+            Fullscreen::Borderless => Some(winit::window::Fullscreen::Borderless(monitor)),
+
+            // Highest resolution, then highest refresh rate
+            Fullscreen::Exclusive  => monitor?.video_modes()
+                .max_by_key(|mode| (mode.size().width * mode.size().height, mode.refresh_rate_millihertz()))
+                .map(winit::window::Fullscreen::Exclusive),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Backend {
+    All,
+    Primary,
+    Vulkan,
+    Metal,
+    Dx12,
+    Dx11,
+    Gl,
+}
+
+impl From<Backend> for wgpu::Backends {
+    fn from(value: Backend) -> Self {
+        return match value {
+            Backend::All     => wgpu::Backends::all(),
+            Backend::Primary => wgpu::Backends::PRIMARY,
+            Backend::Vulkan  => wgpu::Backends::VULKAN,
+            Backend::Metal   => wgpu::Backends::METAL,
+            Backend::Dx12    => wgpu::Backends::DX12,
+            Backend::Dx11    => wgpu::Backends::DX11,
+            Backend::Gl      => wgpu::Backends::GL,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerPreference {
+    LowPower,
+    HighPerformance,
+}
+
+impl From<PowerPreference> for wgpu::PowerPreference {
+    fn from(value: PowerPreference) -> Self {
+        return match value {
+            PowerPreference::LowPower        => wgpu::PowerPreference::LowPower,
+            PowerPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        };
+    }
+}
@@ -0,0 +1,474 @@
+use std::{collections::HashMap, sync::Arc};
+
+use color_eyre::{Result, eyre::bail};
+use image::{DynamicImage, RgbaImage};
+
+use super::{context::GraphicsContext, texture::{Texture, TextureSettings}, primitive::rect::Rect};
+
+#[derive(Debug, Clone, Copy)]
+pub struct AtlasSettings {
+    /// Pages start small and double up to this size before a new page is opened.
+    pub max_page_size : u32,
+    /// Transparent gap around every image.
+    pub padding       : u32,
+    /// Border pixels repeated outwards, keeps filtering at the edges from sampling the gap.
+    pub extrude       : u32,
+    pub texture       : TextureSettings,
+}
+
+impl Default for AtlasSettings {
+    fn default() -> Self {
+        return Self {
+            max_page_size : 2048,
+            padding       : 1,
+            extrude       : 1,
+            texture       : wgpu::FilterMode::Linear.into(),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasRegion {
+    pub page : usize,
+    pub uv   : Rect,
+}
+
+/// Bottom-left skyline packer, tracks the top edge of everything placed so far.
+struct Skyline {
+    width  : u32,
+    height : u32,
+    /// x, y, width of each segment, left to right.
+    nodes  : Vec<(u32, u32, u32)>,
+}
+
+impl Skyline {
+    fn new(width: u32, height: u32) -> Self {
+        return Self { width, height, nodes: vec![(0, 0, width)] };
+    }
+
+    fn grow(&mut self, width: u32, height: u32) {
+        if width > self.width {
+            self.nodes.push((self.width, 0, width - self.width));
+        }
+
+        self.width = width;
+        self.height = height;
+    }
+
+    fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        // Lowest resulting top edge, then narrowest segment
+        let mut best: Option<(usize, u32, u32)> = None;
+        for i in 0 .. self.nodes.len() {
+            let Some(y) = self.fit(i, width, height) else {
+                continue;
+            };
+
+            if best.is_none_or(|(_, best_y, best_width)| y < best_y || (y == best_y && self.nodes[i].2 < best_width)) {
+                best = Some((i, y, self.nodes[i].2));
+            }
+        }
+
+        let (index, y, _) = best?;
+        let x = self.nodes[index].0;
+        self.nodes.insert(index, (x, y + height, width));
+
+        // Cut the segments now covered by the new one
+        let i = index + 1;
+        while i < self.nodes.len() {
+            let (node_x, node_y, node_width) = self.nodes[i];
+            let end = x + width;
+            if node_x >= end {
+                break;
+            }
+
+            if node_x + node_width <= end {
+                self.nodes.remove(i);
+            } else {
+                self.nodes[i] = (end, node_y, node_x + node_width - end);
+                break;
+            }
+        }
+
+        // Merge neighbours at the same height
+        let mut i = 0;
+        while i + 1 < self.nodes.len() {
+            if self.nodes[i].1 == self.nodes[i + 1].1 {
+                self.nodes[i].2 += self.nodes[i + 1].2;
+                self.nodes.remove(i + 1);
+            } else {
+                i += 1;
+            }
+        }
+
+        return Some((x, y));
+    }
+
+    fn fit(&self, index: usize, width: u32, height: u32) -> Option<u32> {
+        let x = self.nodes[index].0;
+        if x + width > self.width {
+            return None;
+        }
+
+        let mut y = 0;
+        let mut remaining = width as i64;
+        for &(_, node_y, node_width) in &self.nodes[index ..] {
+            if remaining <= 0 {
+                break;
+            }
+
+            y = y.max(node_y);
+            remaining -= node_width as i64;
+        }
+
+        return if y + height <= self.height { Some(y) } else { None };
+    }
+}
+
+struct Page {
+    image   : RgbaImage,
+    skyline : Skyline,
+    texture : Option<Arc<Texture>>,
+    dirty   : bool,
+}
+
+impl Page {
+    fn new(size: u32) -> Self {
+        return Self {
+            image   : RgbaImage::new(size, size),
+            skyline : Skyline::new(size, size),
+            texture : None,
+            dirty   : true,
+        };
+    }
+}
+
+/// Packs many small images into a few large textures, so sprites from different images can share a batch.
+/// Images are placed as they are added, pages grow when full and everything is repacked when nothing fits anymore.
+/// Regions move when the atlas repacks, look them up by name after adding images.
+pub struct TextureAtlas {
+    settings : AtlasSettings,
+    pages    : Vec<Page>,
+    images   : HashMap<String, RgbaImage>,
+    /// Page and pixel rect of every image, without padding and extrusion.
+    regions  : HashMap<String, (usize, u32, u32, u32, u32)>,
+}
+
+impl TextureAtlas {
+    const INITIAL_PAGE_SIZE: u32 = 256;
+
+    pub fn new(settings: AtlasSettings) -> Self {
+        return Self {
+            settings,
+            pages   : vec![],
+            images  : HashMap::new(),
+            regions : HashMap::new(),
+        };
+    }
+
+    /// Adds or replaces an image, takes effect on the GPU after the next `upload`.
+    pub fn add(&mut self, name: impl Into<String>, image: &DynamicImage) -> Result<()> {
+        let name = name.into();
+        let image = image.to_rgba8();
+
+        let border = 2 * (self.settings.padding + self.settings.extrude);
+        let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            bail!("Image '{}' is empty ({}x{})", name, width, height);
+        }
+
+        if width + border > self.settings.max_page_size || height + border > self.settings.max_page_size {
+            bail!("Image '{}' ({}x{}) does not fit into an atlas page of {}px", name, width, height, self.settings.max_page_size);
+        }
+
+        let replaced = self.images.insert(name.clone(), image).is_some();
+        if replaced || !self.place(&name, self.pages.is_empty()) {
+            self.repack();
+        }
+
+        return Ok(());
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        let removed = self.images.remove(name).is_some();
+        if removed {
+            self.repack();
+        }
+
+        return removed;
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        return self.regions.contains_key(name);
+    }
+
+    /// Packs all images again from scratch, largest first.
+    pub fn repack(&mut self) {
+        self.pages.clear();
+        self.regions.clear();
+
+        let mut names = self.images.keys().cloned().collect::<Vec<_>>();
+        names.sort_by_key(|name| {
+            let (width, height) = self.images[name].dimensions();
+            return (std::cmp::Reverse(width.max(height)), std::cmp::Reverse(width * height), name.clone());
+        });
+
+        for name in names {
+            // Every image fits into an empty page, checked in `add`
+            let placed = self.place(&name, true);
+            debug_assert!(placed);
+        }
+    }
+
+    /// Places an image in the first page with room, growing pages before opening a new one.
+    /// Returns `false` if that would need a new page and `new_page` is not set.
+    fn place(&mut self, name: &str, new_page: bool) -> bool {
+        let border = 2 * (self.settings.padding + self.settings.extrude);
+        let (width, height) = self.images[name].dimensions();
+        let (cell_width, cell_height) = (width + border, height + border);
+
+        for index in 0 .. self.pages.len() {
+            loop {
+                if let Some((x, y)) = self.pages[index].skyline.insert(cell_width, cell_height) {
+                    self.blit(index, name, x, y);
+                    return true;
+                }
+
+                if !self.grow(index) {
+                    break;
+                }
+            }
+        }
+
+        // Repacking might still fit everything into the existing pages
+        if !new_page {
+            return false;
+        }
+
+        let mut size = Self::INITIAL_PAGE_SIZE.min(self.settings.max_page_size);
+        while cell_width > size || cell_height > size {
+            size *= 2;
+        }
+
+        let mut page = Page::new(size.min(self.settings.max_page_size));
+        let (x, y) = page.skyline.insert(cell_width, cell_height).unwrap();
+        self.pages.push(page);
+        self.blit(self.pages.len() - 1, name, x, y);
+
+        return true;
+    }
+
+    fn grow(&mut self, index: usize) -> bool {
+        let page = &mut self.pages[index];
+        let size = page.image.width();
+        if size >= self.settings.max_page_size {
+            return false;
+        }
+
+        let size = (size * 2).min(self.settings.max_page_size);
+        let mut image = RgbaImage::new(size, size);
+        image::imageops::replace(&mut image, &page.image, 0, 0);
+
+        page.image = image;
+        page.skyline.grow(size, size);
+        page.dirty = true;
+
+        return true;
+    }
+
+    /// Copies an image into its cell, repeating the border pixels into the extrusion.
+    fn blit(&mut self, index: usize, name: &str, x: u32, y: u32) {
+        let image = &self.images[name];
+        let page = &mut self.pages[index];
+        let offset = self.settings.padding + self.settings.extrude;
+        let extrude = self.settings.extrude as i64;
+        let (width, height) = image.dimensions();
+
+        for row in -extrude .. height as i64 + extrude {
+            for column in -extrude .. width as i64 + extrude {
+                let source = image.get_pixel(
+                    column.clamp(0, width as i64 - 1) as u32,
+                    row.clamp(0, height as i64 - 1) as u32,
+                );
+
+                let target_x = (x + offset) as i64 + column;
+                let target_y = (y + offset) as i64 + row;
+                page.image.put_pixel(target_x as u32, target_y as u32, *source);
+            }
+        }
+
+        page.dirty = true;
+        self.regions.insert(name.to_owned(), (index, x + offset, y + offset, width, height));
+    }
+
+    /// Sends changed pages to the GPU. Pages that grew get a new texture.
+    pub fn upload(&mut self, graphics: &GraphicsContext) -> Result<()> {
+        for page in self.pages.iter_mut().filter(|page| page.dirty) {
+            let (width, height) = page.image.dimensions();
+
+            match &page.texture {
+                Some(texture) if texture.size() == (width, height) => {
+                    texture.write(graphics, &page.image)?;
+                }
+
+                _ => {
+                    let image = DynamicImage::ImageRgba8(page.image.clone());
+                    page.texture = Some(Arc::new(Texture::from_image(graphics, &image, self.settings.texture, Some("atlas_page"))?));
+                }
+            }
+
+            page.dirty = false;
+        }
+
+        return Ok(());
+    }
+
+    pub fn page_count(&self) -> usize {
+        return self.pages.len();
+    }
+
+    /// `None` until the page was uploaded.
+    pub fn page(&self, index: usize) -> Option<&Arc<Texture>> {
+        return self.pages.get(index)?.texture.as_ref();
+    }
+
+    pub fn region(&self, name: &str) -> Option<AtlasRegion> {
+        let &(page, x, y, width, height) = self.regions.get(name)?;
+        let size = self.pages[page].image.width() as f32;
+
+        return Some(AtlasRegion {
+            page,
+            uv: Rect::new(x as f32 / size, y as f32 / size, width as f32 / size, height as f32 / size),
+        });
+    }
+
+    /// Page texture and UV rect, ready for `SpriteBatch::draw`.
+    pub fn get(&self, name: &str) -> Option<(&Arc<Texture>, Rect)> {
+        let region = self.region(name)?;
+        return Some((self.page(region.page)?, region.uv));
+    }
+
+    /// Size of an image in pixels.
+    pub fn size(&self, name: &str) -> Option<(u32, u32)> {
+        return self.images.get(name).map(|image| image.dimensions());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overlaps(a: (u32, u32, u32, u32), b: (u32, u32, u32, u32)) -> bool {
+        return a.0 < b.0 + b.2 && b.0 < a.0 + a.2 && a.1 < b.1 + b.3 && b.1 < a.1 + a.3;
+    }
+
+    fn image(width: u32, height: u32) -> DynamicImage {
+        return DynamicImage::ImageRgba8(RgbaImage::new(width, height));
+    }
+
+    /// Every cell including padding and extrusion lies inside its page and overlaps no other cell on it.
+    fn assert_packed(atlas: &TextureAtlas) {
+        let offset = atlas.settings.padding + atlas.settings.extrude;
+        let cells = atlas.regions.values()
+            .map(|&(page, x, y, width, height)| (page, (x - offset, y - offset, width + 2 * offset, height + 2 * offset)))
+            .collect::<Vec<_>>();
+
+        for (i, &(page, cell)) in cells.iter().enumerate() {
+            let size = atlas.pages[page].image.width();
+            assert!(cell.0 + cell.2 <= size && cell.1 + cell.3 <= size, "{cell:?} is outside of the {size}px page");
+
+            for &(other_page, other) in &cells[i + 1 ..] {
+                assert!(page != other_page || !overlaps(cell, other), "{cell:?} overlaps {other:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn skyline_does_not_overlap() {
+        let mut skyline = Skyline::new(64, 64);
+        let mut placed = vec![];
+
+        for (width, height) in [(10, 20), (30, 5), (7, 7), (20, 20), (64, 3), (5, 30), (12, 9), (9, 12)] {
+            let (x, y) = skyline.insert(width, height).unwrap();
+            assert!(x + width <= 64 && y + height <= 64);
+
+            for &other in &placed {
+                assert!(!overlaps((x, y, width, height), other), "{:?} overlaps {:?}", (x, y, width, height), other);
+            }
+
+            placed.push((x, y, width, height));
+        }
+    }
+
+    #[test]
+    fn skyline_rejects_what_does_not_fit() {
+        let mut skyline = Skyline::new(16, 16);
+        assert_eq!(skyline.insert(17, 1), None);
+        assert_eq!(skyline.insert(16, 16), Some((0, 0)));
+        assert_eq!(skyline.insert(1, 1), None);
+    }
+
+    #[test]
+    fn skyline_grows() {
+        let mut skyline = Skyline::new(16, 16);
+        skyline.insert(16, 16).unwrap();
+
+        skyline.grow(32, 32);
+        assert_eq!(skyline.insert(16, 32), Some((16, 0)));
+        assert_eq!(skyline.insert(16, 16), Some((0, 16)));
+        assert_eq!(skyline.insert(1, 1), None);
+    }
+
+    #[test]
+    fn rejects_empty_and_oversized_images() {
+        let mut atlas = TextureAtlas::new(AtlasSettings { max_page_size: 64, ..Default::default() });
+        assert!(atlas.add("empty", &image(0, 8)).is_err());
+        assert!(atlas.add("flat", &image(8, 0)).is_err());
+        assert!(atlas.add("huge", &image(63, 8)).is_err());
+        assert_eq!(atlas.page_count(), 0);
+    }
+
+    #[test]
+    fn grows_the_page_before_opening_another() {
+        let mut atlas = TextureAtlas::new(AtlasSettings { max_page_size: 1024, ..Default::default() });
+        for i in 0 .. 8 {
+            atlas.add(format!("image{i}"), &image(100, 100)).unwrap();
+        }
+
+        assert_eq!(atlas.page_count(), 1);
+        assert_eq!(atlas.pages[0].image.width(), 512);
+        assert_packed(&atlas);
+    }
+
+    #[test]
+    fn opens_a_new_page_when_full() {
+        let mut atlas = TextureAtlas::new(AtlasSettings { max_page_size: 64, ..Default::default() });
+        for i in 0 .. 8 {
+            atlas.add(format!("image{i}"), &image(28, 28)).unwrap();
+        }
+
+        // Four 32px cells fill a 64px page
+        assert_eq!(atlas.page_count(), 2);
+        assert!((0 .. 8).all(|i| atlas.contains(&format!("image{i}"))));
+        assert_packed(&atlas);
+    }
+
+    #[test]
+    fn repacks_after_removing_and_replacing() {
+        let mut atlas = TextureAtlas::new(AtlasSettings { max_page_size: 64, ..Default::default() });
+        for i in 0 .. 8 {
+            atlas.add(format!("image{i}"), &image(28, 28)).unwrap();
+        }
+
+        for i in 0 .. 4 {
+            assert!(atlas.remove(&format!("image{i}")));
+        }
+
+        assert!(!atlas.remove("image0"));
+        assert_eq!(atlas.page_count(), 1);
+
+        atlas.add("image4", &image(10, 40)).unwrap();
+        assert_eq!(atlas.size("image4"), Some((10, 40)));
+        assert!((4 .. 8).all(|i| atlas.contains(&format!("image{i}"))));
+        assert_packed(&atlas);
+    }
+}
\ No newline at end of file
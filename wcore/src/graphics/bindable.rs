@@ -1,8 +1,7 @@
-use bytemuck::{Pod, Zeroable};
-
-pub trait Bindable<T: Default + Clone + Pod + Zeroable = [[f32; 4]; 4]> {
+pub trait Bindable {
     fn bind<'a, 'b: 'a>(&'b self, render_pass: &mut wgpu::RenderPass<'a>, index: u32);
     fn layout(&self) -> &wgpu::BindGroupLayout;
+    /// Entries `layout` was made from, pipelines are described and cached by them.
+    fn layout_entries(&self) -> &'static [wgpu::BindGroupLayoutEntry];
     fn group(&self) -> &wgpu::BindGroup;
-    fn update(&self, queue: &wgpu::Queue, value: &T);
 }
\ No newline at end of file
@@ -64,6 +64,7 @@ impl Transformation for Camera3D {
 /* Generic porojection matrix */
 pub trait Projection {
     fn resize(&mut self, width: u32, height: u32);
+    fn rescale(&mut self, _scale_factor: f64) {}
 }
 
 /* Perspective projection matrix */
@@ -98,22 +99,30 @@ impl Transformation for ProjectionPerspective {
 }
 
 /* Orthographic projection matrix */
+/// Takes the size in physical pixels, but projects logical units.
 pub struct ProjectionOrthographic {
     width: f32,
     height: f32,
+    scale: f32,
     znear: f32,
     zfar: f32,
 }
 
 impl ProjectionOrthographic {
-    pub fn new(width: u32, height: u32, znear: f32, zfar: f32) -> Self {
+    pub fn new(width: u32, height: u32, scale_factor: f64, znear: f32, zfar: f32) -> Self {
         return Self {
             width: width as f32,
             height: height as f32,
+            scale: scale_factor as f32,
             znear,
             zfar,
         };
     }
+
+    /// Visible area in logical units.
+    pub fn logical_size(&self) -> (f32, f32) {
+        return (self.width / self.scale, self.height / self.scale);
+    }
 }
 
 impl Projection for ProjectionOrthographic {
@@ -121,10 +130,15 @@ impl Projection for ProjectionOrthographic {
         self.width = width as f32;
         self.height = height as f32;
     }
+
+    fn rescale(&mut self, scale_factor: f64) {
+        self.scale = scale_factor as f32;
+    }
 }
 
 impl Transformation for ProjectionOrthographic {
     fn apply(&self) -> Matrix4<f32> {
-        return cgmath::ortho(0.0, self.width, self.height, 0.0, self.znear, self.zfar);
+        let (width, height) = self.logical_size();
+        return cgmath::ortho(0.0, width, height, 0.0, self.znear, self.zfar);
     }
 }
\ No newline at end of file
@@ -0,0 +1,246 @@
+use std::fs;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::mpsc;
+
+use color_eyre::eyre::{Report, Result};
+use image::RgbaImage;
+
+use super::utils;
+
+/// Reads a texture back into an sRGB image.
+/// The texture must have been created with `TextureUsages::COPY_SRC`.
+/// Linear formats are encoded to sRGB so the result matches what an sRGB surface would show.
+pub fn capture(device  : &wgpu::Device,
+               queue   : &wgpu::Queue,
+               texture : &wgpu::Texture,
+               format  : wgpu::TextureFormat,
+               width   : u32,
+               height  : u32) -> Result<RgbaImage> {
+    let bytes_per_pixel = bytes_per_pixel(format)
+        .ok_or_else(|| Report::msg(format!("Capturing {format:?} textures is not supported")))?;
+
+    // Rows in the buffer have to be aligned, the padding is stripped when converting
+    let unpadded_bytes_per_row = width * bytes_per_pixel;
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(alignment) * alignment;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label              : Some("Capture Buffer"),
+        size               : padded_bytes_per_row as u64 * height as u64,
+        usage              : wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation : false,
+    });
+
+    utils::submit(queue, device, |encoder| {
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                aspect    : wgpu::TextureAspect::All,
+                texture,
+                mip_level : 0,
+                origin    : wgpu::Origin3d::ZERO,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer : &buffer,
+                layout : wgpu::ImageDataLayout {
+                    offset         : 0,
+                    bytes_per_row  : NonZeroU32::new(padded_bytes_per_row),
+                    rows_per_image : NonZeroU32::new(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+    });
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| { sender.send(result).ok(); });
+    device.poll(wgpu::Maintain::Wait);
+    receiver.recv()??;
+
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    {
+        let data = slice.get_mapped_range();
+        convert_rows(&data, padded_bytes_per_row as usize, unpadded_bytes_per_row as usize, format, &mut pixels);
+    }
+
+    buffer.unmap();
+    return RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| Report::msg("Captured data does not match image size"));
+}
+
+/// Saves a captured image, creating parent directories as needed.
+/// The format is deduced from the extension.
+pub fn save(image: &RgbaImage, path: impl AsRef<Path>) -> Result<()> {
+    if let Some(parent) = path.as_ref().parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    image.save(path)?;
+    return Ok(());
+}
+
+fn bytes_per_pixel(format: wgpu::TextureFormat) -> Option<u32> {
+    use wgpu::TextureFormat::*;
+    return match format {
+        Rgba8Unorm  | Rgba8UnormSrgb | Bgra8Unorm | Bgra8UnormSrgb => Some(4),
+        Rgba16Float => Some(8),
+        Rgba32Float => Some(16),
+        _ => None,
+    };
+}
+
+/// Rows of a copy are padded to `wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`, only the start of each holds pixels.
+fn convert_rows(data: &[u8], padded_bytes_per_row: usize, unpadded_bytes_per_row: usize, format: wgpu::TextureFormat, out: &mut Vec<u8>) {
+    for row in data.chunks(padded_bytes_per_row) {
+        convert_row(&row[.. unpadded_bytes_per_row], format, out);
+    }
+}
+
+fn convert_row(row: &[u8], format: wgpu::TextureFormat, out: &mut Vec<u8>) {
+    use wgpu::TextureFormat::*;
+    match format {
+        Rgba8UnormSrgb => out.extend_from_slice(row),
+        Bgra8UnormSrgb => for px in row.chunks_exact(4) {
+            out.extend_from_slice(&[px[2], px[1], px[0], px[3]]);
+        },
+
+        Rgba8Unorm => for px in row.chunks_exact(4) {
+            out.extend_from_slice(&[encode(px[0] as f32 / 255.0), encode(px[1] as f32 / 255.0), encode(px[2] as f32 / 255.0), px[3]]);
+        },
+
+        Bgra8Unorm => for px in row.chunks_exact(4) {
+            out.extend_from_slice(&[encode(px[2] as f32 / 255.0), encode(px[1] as f32 / 255.0), encode(px[0] as f32 / 255.0), px[3]]);
+        },
+
+        Rgba16Float => for px in row.chunks_exact(8) {
+            let channel = |i: usize| f16_to_f32(u16::from_le_bytes([px[i * 2], px[i * 2 + 1]]));
+            out.extend_from_slice(&[encode(channel(0)), encode(channel(1)), encode(channel(2)), quantize(channel(3))]);
+        },
+
+        Rgba32Float => for px in row.chunks_exact(16) {
+            let channel = |i: usize| f32::from_le_bytes([px[i * 4], px[i * 4 + 1], px[i * 4 + 2], px[i * 4 + 3]]);
+            out.extend_from_slice(&[encode(channel(0)), encode(channel(1)), encode(channel(2)), quantize(channel(3))]);
+        },
+
+        _ => unreachable!(),
+    }
+}
+
+/// Linear to sRGB transfer function.
+fn encode(linear: f32) -> u8 {
+    let linear = linear.clamp(0.0, 1.0);
+    let srgb = if linear <= 0.0031308 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    };
+
+    return quantize(srgb);
+}
+
+fn quantize(value: f32) -> u8 {
+    return (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1F) as i32;
+    let mantissa = (bits & 0x3FF) as f32;
+
+    return sign * match exponent {
+        0  => mantissa * 2f32.powi(-24),
+        31 => if mantissa == 0.0 { f32::INFINITY } else { f32::NAN },
+        _  => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert(format: wgpu::TextureFormat, row: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        convert_row(row, format, &mut out);
+        return out;
+    }
+
+    fn f16_pixel(channels: [u16; 4]) -> Vec<u8> {
+        return channels.iter().flat_map(|channel| channel.to_le_bytes()).collect();
+    }
+
+    fn f32_pixel(channels: [f32; 4]) -> Vec<u8> {
+        return channels.iter().flat_map(|channel| channel.to_le_bytes()).collect();
+    }
+
+    #[test]
+    fn converts_srgb_formats() {
+        use wgpu::TextureFormat::*;
+        assert_eq!(convert(Rgba8UnormSrgb, &[1, 2, 3, 4, 5, 6, 7, 8]), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(convert(Bgra8UnormSrgb, &[1, 2, 3, 4, 5, 6, 7, 8]), [3, 2, 1, 4, 7, 6, 5, 8]);
+    }
+
+    #[test]
+    fn encodes_linear_formats() {
+        use wgpu::TextureFormat::*;
+
+        // Color channels are encoded to sRGB, alpha is stored as is
+        assert_eq!(convert(Rgba8Unorm, &[0, 128, 255, 128]), [0, 188, 255, 128]);
+        assert_eq!(convert(Bgra8Unorm, &[0, 128, 255, 128]), [255, 188, 0, 128]);
+
+        // 1.0, 0.5, 0.0 and half alpha
+        assert_eq!(convert(Rgba16Float, &f16_pixel([0x3C00, 0x3800, 0x0000, 0x3800])), [255, 188, 0, 128]);
+        assert_eq!(convert(Rgba32Float, &f32_pixel([1.0, 0.5, 0.0, 0.5])), [255, 188, 0, 128]);
+
+        // HDR and negative values are clamped
+        assert_eq!(convert(Rgba32Float, &f32_pixel([4.0, -1.0, 0.25, 2.0])), [255, 0, 137, 255]);
+    }
+
+    #[test]
+    fn strips_row_padding() {
+        // Two rows of two pixels, padded to 12 bytes
+        let data = [
+            1, 2, 3, 4, 5, 6, 7, 8, 0xEE, 0xEE, 0xEE, 0xEE,
+            9, 10, 11, 12, 13, 14, 15, 16, 0xEE, 0xEE, 0xEE, 0xEE,
+        ];
+
+        let mut out = vec![];
+        convert_rows(&data, 12, 8, wgpu::TextureFormat::Bgra8UnormSrgb, &mut out);
+        assert_eq!(out, [3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]);
+    }
+
+    #[test]
+    fn decodes_half_floats() {
+        assert_eq!(f16_to_f32(0x3C00), 1.0);
+        assert_eq!(f16_to_f32(0xC000), -2.0);
+        assert_eq!(f16_to_f32(0x7BFF), 65504.0);
+        assert_eq!(f16_to_f32(0x0000), 0.0);
+        assert!(f16_to_f32(0x8000).is_sign_negative());
+
+        // Subnormals have no implicit leading one
+        assert_eq!(f16_to_f32(0x0001), 2f32.powi(-24));
+        assert_eq!(f16_to_f32(0x03FF), 1023.0 * 2f32.powi(-24));
+
+        assert_eq!(f16_to_f32(0x7C00), f32::INFINITY);
+        assert_eq!(f16_to_f32(0xFC00), f32::NEG_INFINITY);
+        assert!(f16_to_f32(0x7E00).is_nan());
+        assert!(f16_to_f32(0x7C01).is_nan());
+    }
+
+    #[test]
+    fn encodes_srgb() {
+        assert_eq!(encode(0.0), 0);
+        // Last value on the linear segment, 0.0031308 * 12.92 = 0.04045
+        assert_eq!(encode(0.0031308), 10);
+        assert_eq!(encode(0.5), 188);
+        assert_eq!(encode(1.0), 255);
+
+        assert_eq!(encode(-1.0), 0);
+        assert_eq!(encode(2.0), 255);
+        assert_eq!(encode(f32::INFINITY), 255);
+    }
+}
@@ -1,39 +1,58 @@
+use std::{cell::OnceCell, path::PathBuf};
+
+use color_eyre::eyre::Result;
+use log::warn;
+use image::RgbaImage;
 use winit::window::Window;
 
+use crate::{AppConfig, handle::AppHandle, timing::FrameStats};
+
+use super::{capture, sampler::SamplerCache, mipmap::MipmapGenerator, texture::Texture, postprocess::FullscreenPass, bindable::Bindable, target::RenderTarget, pipeline::{Pipeline, shader::reload::ShaderWatcher, builder::PipelineCache}, pass::RenderPassBuilder, utils::{self, ColorTarget}};
+
 pub struct GraphicsContext {
-    pub device  : wgpu::Device,
-    pub surface : wgpu::Surface,
-    pub queue   : wgpu::Queue,
+    pub device    : wgpu::Device,
+    pub surface   : Option<wgpu::Surface>,
+    pub offscreen : Option<wgpu::Texture>,
+    pub queue     : wgpu::Queue,
 
     pub surface_configuration : wgpu::SurfaceConfiguration,
     pub scale_factor          : f64,
+    /// Color the frame is cleared to before the screens render.
+    pub clear_color           : wgpu::Color,
+    /// MSAA samples of the pipelines and attachments, 1 or 4.
+    pub sample_count          : u32,
+
+    /// Window and application requests from screens.
+    pub app         : AppHandle,
+    pub frame_stats : FrameStats,
+    pub samplers    : SamplerCache,
+    /// Pipelines rebuilt when their WGSL files change, in debug builds.
+    pub shaders     : ShaderWatcher,
+    pub pipelines   : PipelineCache,
+
+    screenshot : Option<PathBuf>,
+    mipmaps    : OnceCell<MipmapGenerator>,
+    blit       : OnceCell<FullscreenPass>,
+    /// Multisampled color attachment sized to the surface, resolved into the frame.
+    msaa       : Option<(wgpu::Texture, wgpu::TextureView)>,
+    /// Depth buffer sized to the surface, created on first use.
+    depth      : OnceCell<(wgpu::Texture, wgpu::TextureView)>,
 }
 
 impl GraphicsContext {
-    pub async fn new(window: &Window) -> Option<Self> {
+    pub async fn new(window: &Window, config: &AppConfig) -> Option<Self> {
         // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
-        let instance = wgpu::Instance::new(wgpu::Backends::all());
+        let instance = wgpu::Instance::new(config.backend.into());
         let surface = unsafe { instance.create_surface(window) };
         let adapter = instance.request_adapter(
             &wgpu::RequestAdapterOptions {
-                power_preference       : wgpu::PowerPreference::HighPerformance,
+                power_preference       : config.power_preference.into(),
                 compatible_surface     : Some(&surface),
                 force_fallback_adapter : false,
             },
         ).await?;
 
-        let (device, queue) = adapter.request_device(
-            &wgpu::DeviceDescriptor {
-                label    : None,
-                features : wgpu::Features::empty(),
-                limits   : {
-                    let mut limits = wgpu::Limits::default();
-                    limits.max_bind_groups = 8;
-                    limits 
-                },
-            },
-            None,
-        ).await.ok()?;
+        let (device, queue) = Self::request_device(&adapter).await?;
 
         let size = window.inner_size();
         let surface_configuration = wgpu::SurfaceConfiguration {
@@ -41,18 +60,284 @@ impl GraphicsContext {
             format       : *surface.get_supported_formats(&adapter).first()?,
             width        : size.width,
             height       : size.height,
-            present_mode : wgpu::PresentMode::AutoVsync,
+            present_mode : config.present_mode.into(),
             alpha_mode   : wgpu::CompositeAlphaMode::Auto,
         }; surface.configure(&device, &surface_configuration);
 
+        let sample_count = Self::supported_sample_count(config.samples);
+        let msaa = Self::make_msaa(&device, &surface_configuration, sample_count);
+
         let scale_factor = window.scale_factor();
         return Some(Self {
-            surface,
+            surface   : Some(surface),
+            offscreen : None,
             device,
             queue,
 
             surface_configuration,
             scale_factor,
+            clear_color: utils::CLEAR_COLOR,
+            sample_count,
+
+            app         : AppHandle::default(),
+            frame_stats : FrameStats::default(),
+            samplers    : SamplerCache::default(),
+            shaders     : ShaderWatcher::default(),
+            pipelines   : PipelineCache::default(),
+            screenshot  : None,
+            mipmaps     : OnceCell::new(),
+            blit        : OnceCell::new(),
+            msaa,
+            depth       : OnceCell::new(),
+        });
+    }
+
+    /// Replaces the device and surface after the device was lost, keeping the settings and pending requests.
+    /// The pipeline cache belongs to the old device and starts empty, screens rebuild their resources in `Screen::recreate`.
+    /// Returns false if no new context could be created, the old surface is gone either way.
+    pub async fn recreate(&mut self, window: &Window, config: &AppConfig) -> bool {
+        // A window only has one surface at a time
+        self.surface.take();
+
+        let Some(mut context) = Self::new(window, config).await else {
+            return false;
+        };
+
+        context.scale_factor = self.scale_factor;
+        context.clear_color = self.clear_color;
+        context.app = std::mem::take(&mut self.app);
+        context.frame_stats = std::mem::take(&mut self.frame_stats);
+        context.shaders = std::mem::take(&mut self.shaders);
+        context.screenshot = self.screenshot.take();
+
+        *self = context;
+        return true;
+    }
+
+    /// Creates a context without a window, rendering into an offscreen texture.
+    /// Falls back to a software adapter if no hardware one is available.
+    pub async fn headless(config: &AppConfig) -> Option<Self> {
+        let instance = wgpu::Instance::new(config.backend.into());
+        let mut adapter = None;
+        for force_fallback_adapter in [false, true] {
+            adapter = instance.request_adapter(
+                &wgpu::RequestAdapterOptions {
+                    power_preference   : config.power_preference.into(),
+                    compatible_surface : None,
+                    force_fallback_adapter,
+                },
+            ).await;
+
+            if adapter.is_some() {
+                break;
+            }
+        }
+
+        let adapter = adapter?;
+        let (device, queue) = Self::request_device(&adapter).await?;
+
+        // Not used to configure anything, but pipelines read format and size from it
+        let surface_configuration = wgpu::SurfaceConfiguration {
+            usage        : wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format       : wgpu::TextureFormat::Rgba8UnormSrgb,
+            width        : config.width.max(1),
+            height       : config.height.max(1),
+            present_mode : config.present_mode.into(),
+            alpha_mode   : wgpu::CompositeAlphaMode::Auto,
+        };
+
+        let sample_count = Self::supported_sample_count(config.samples);
+        let msaa = Self::make_msaa(&device, &surface_configuration, sample_count);
+
+        let offscreen = Self::make_offscreen(&device, &surface_configuration);
+        return Some(Self {
+            surface   : None,
+            offscreen : Some(offscreen),
+            device,
+            queue,
+
+            surface_configuration,
+            scale_factor: 1.0,
+            clear_color: utils::CLEAR_COLOR,
+            sample_count,
+
+            app         : AppHandle::default(),
+            frame_stats : FrameStats::default(),
+            samplers    : SamplerCache::default(),
+            shaders     : ShaderWatcher::default(),
+            pipelines   : PipelineCache::default(),
+            screenshot  : None,
+            mipmaps     : OnceCell::new(),
+            blit        : OnceCell::new(),
+            msaa,
+            depth       : OnceCell::new(),
+        });
+    }
+
+    /// Created on first use, shared by all textures.
+    pub fn mipmap_generator(&self) -> &MipmapGenerator {
+        return self.mipmaps.get_or_init(|| MipmapGenerator::new(&self.device, Texture::FORMAT));
+    }
+
+    pub fn is_headless(&self) -> bool {
+        return self.surface.is_none();
+    }
+
+    /// Reconfigures the surface, or recreates the offscreen texture in headless mode.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        self.surface_configuration.width = width;
+        self.surface_configuration.height = height;
+
+        self.reconfigure();
+
+        if self.offscreen.is_some() {
+            self.offscreen = Some(Self::make_offscreen(&self.device, &self.surface_configuration));
+        }
+
+        self.msaa = Self::make_msaa(&self.device, &self.surface_configuration, self.sample_count);
+        self.depth.take();
+    }
+
+    /// Color attachment for rendering into the frame `view`.
+    /// With MSAA this is the multisampled texture, resolved into `view` at the end of the pass.
+    pub fn attachment<'a>(&'a self, view: &'a wgpu::TextureView) -> ColorTarget<'a> {
+        return match &self.msaa {
+            Some((_, msaa)) => ColorTarget { view: msaa, resolve_target: Some(view) },
+            None            => ColorTarget::from(view),
+        };
+    }
+
+    /// Reconfigures the surface with the current configuration, e.g. after it was lost.
+    pub fn reconfigure(&self) {
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.surface_configuration);
+        }
+    }
+
+    /// Surface size in logical pixels.
+    pub fn logical_size(&self) -> (f32, f32) {
+        let scale_factor = self.scale_factor as f32;
+        return (self.surface_configuration.width  as f32 / scale_factor,
+                self.surface_configuration.height as f32 / scale_factor);
+    }
+
+    /// Saves the next rendered frame as a PNG.
+    pub fn request_screenshot(&mut self, path: impl Into<PathBuf>) {
+        self.screenshot = Some(path.into());
+    }
+
+    pub(crate) fn take_screenshot(&mut self) -> Option<PathBuf> {
+        return self.screenshot.take();
+    }
+
+    /// Texture the frame is rendered into when a screenshot is taken, since surfaces cannot be read back on every backend.
+    pub(crate) fn make_screenshot_target(&self) -> Texture {
+        let config = &self.surface_configuration;
+        return Texture::with_format(self, config.width, config.height, config.format, wgpu::FilterMode::Nearest, Some("Screenshot"));
+    }
+
+    /// Copies a texture with the same format and size as the surface into the frame `view`, replacing its content.
+    pub fn blit(&self, texture: &Texture, view: &wgpu::TextureView) {
+        let copy = self.blit.get_or_init(|| FullscreenPass::copy(self));
+        utils::submit(&self.queue, &self.device, |encoder| copy.draw(encoder, view, &[texture.group()]));
+    }
+
+    /// Reads back a texture with the same format and size as the surface.
+    pub fn capture(&self, texture: &wgpu::Texture) -> Result<RgbaImage> {
+        let config = &self.surface_configuration;
+        return capture::capture(&self.device, &self.queue, texture, config.format, config.width, config.height);
+    }
+
+    async fn request_device(adapter: &wgpu::Adapter) -> Option<(wgpu::Device, wgpu::Queue)> {
+        // Software adapters often only support downlevel limits
+        for base in [wgpu::Limits::default(), wgpu::Limits::downlevel_defaults()] {
+            let device = adapter.request_device(
+                &wgpu::DeviceDescriptor {
+                    label    : None,
+                    // Optional, pipelines using push constants check for it
+                    features : adapter.features() & wgpu::Features::PUSH_CONSTANTS,
+                    limits   : {
+                        let mut limits = base;
+                        limits.max_bind_groups = 8;
+                        limits.max_push_constant_size = adapter.limits().max_push_constant_size;
+                        limits
+                    },
+                },
+                None,
+            ).await;
+
+            if device.is_ok() {
+                return device.ok();
+            }
+        }
+
+        return None;
+    }
+
+    /// Clears the frame once before the screens render, they draw over each other after that.
+    pub fn clear_frame(&self, view: &wgpu::TextureView) {
+        utils::submit(&self.queue, &self.device, |encoder| {
+            RenderPassBuilder::new(self.attachment(view))
+                .label("Clear Pass")
+                .clear(self.clear_color)
+                .begin(encoder);
+        });
+    }
+
+    /// Depth buffer of the frame, with the sample count of the context.
+    pub fn depth_view(&self) -> &wgpu::TextureView {
+        let (_, view) = self.depth.get_or_init(|| {
+            let size = (self.surface_configuration.width, self.surface_configuration.height);
+            return utils::attachment(&self.device, size, RenderTarget::DEPTH_FORMAT, self.sample_count, "Depth Buffer");
+        });
+
+        return view;
+    }
+
+    /// The depth buffer if `pipeline` uses depth, for passes into the frame.
+    pub fn depth_for(&self, pipeline: &(impl Pipeline + ?Sized)) -> Option<&wgpu::TextureView> {
+        return pipeline.depth().then(|| self.depth_view());
+    }
+
+    /// Every adapter supports 1 and 4 samples, other counts need adapter specific features.
+    fn supported_sample_count(samples: u32) -> u32 {
+        return match samples {
+            0 | 1 => 1,
+            4     => 4,
+            _     => {
+                warn!("{} MSAA samples are not supported, using 4", samples);
+                4
+            }
+        };
+    }
+
+    fn make_msaa(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration, sample_count: u32) -> Option<(wgpu::Texture, wgpu::TextureView)> {
+        if sample_count <= 1 {
+            return None;
+        }
+
+        return Some(utils::attachment(device, (config.width, config.height), config.format, sample_count, "Multisampled Frame"));
+    }
+
+    pub(crate) fn make_offscreen(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+        return device.create_texture(&wgpu::TextureDescriptor {
+            label           : Some("Offscreen Target"),
+            size            : wgpu::Extent3d {
+                width                 : config.width,
+                height                : config.height,
+                depth_or_array_layers : 1,
+            },
+            mip_level_count : 1,
+            sample_count    : 1,
+            dimension       : wgpu::TextureDimension::D2,
+            format          : config.format,
+            usage           : wgpu::TextureUsages::RENDER_ATTACHMENT
+                            | wgpu::TextureUsages::TEXTURE_BINDING
+                            | wgpu::TextureUsages::COPY_SRC,
         });
     }
-}
\ No newline at end of file
+}
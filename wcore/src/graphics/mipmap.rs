@@ -0,0 +1,125 @@
+use wgpu::include_wgsl;
+
+/// Fills the mip chain of a texture by rendering every level from the one above with a linear filter.
+pub struct MipmapGenerator {
+    pipeline : wgpu::RenderPipeline,
+    layout   : wgpu::BindGroupLayout,
+    sampler  : wgpu::Sampler,
+}
+
+impl MipmapGenerator {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("mipmap.wgsl"));
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label   : Some("mipmap_bind_group_layout"),
+            entries : &[
+                wgpu::BindGroupLayoutEntry {
+                    binding    : 0,
+                    visibility : wgpu::ShaderStages::FRAGMENT,
+                    ty         : wgpu::BindingType::Texture {
+                        multisampled   : false,
+                        view_dimension : wgpu::TextureViewDimension::D2,
+                        sample_type    : wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count      : None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding    : 1,
+                    visibility : wgpu::ShaderStages::FRAGMENT,
+                    ty         : wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count      : None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label                : Some("Mipmap Pipeline Layout"),
+            bind_group_layouts   : &[&layout],
+            push_constant_ranges : &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label  : Some("Mipmap Pipeline"),
+            layout : Some(&pipeline_layout),
+
+            vertex: wgpu::VertexState {
+                module      : &shader,
+                entry_point : "vertex_main",
+                buffers     : &[],
+            },
+
+            fragment: Some(wgpu::FragmentState {
+                module      : &shader,
+                entry_point : "fragment_main",
+                targets     : &[Some(format.into())],
+            }),
+
+            primitive     : wgpu::PrimitiveState::default(),
+            depth_stencil : None,
+            multisample   : wgpu::MultisampleState::default(),
+            multiview     : None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label      : Some("Mipmap Sampler"),
+            mag_filter : wgpu::FilterMode::Linear,
+            min_filter : wgpu::FilterMode::Linear,
+            .. Default::default()
+        });
+
+        return Self { pipeline, layout, sampler };
+    }
+
+    /// The texture needs `RENDER_ATTACHMENT` usage and the format this generator was created with.
+    pub fn generate(&self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture, mip_level_count: u32) {
+        let views = (0 .. mip_level_count).map(|level| texture.create_view(&wgpu::TextureViewDescriptor {
+            label           : Some("Mip Level"),
+            base_mip_level  : level,
+            mip_level_count : std::num::NonZeroU32::new(1),
+            .. Default::default()
+        })).collect::<Vec<_>>();
+
+        super::utils::submit(queue, device, |encoder| {
+            for level in 1 .. mip_level_count as usize {
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label   : None,
+                    layout  : &self.layout,
+                    entries : &[
+                        wgpu::BindGroupEntry {
+                            binding  : 0,
+                            resource : wgpu::BindingResource::TextureView(&views[level - 1]),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding  : 1,
+                            resource : wgpu::BindingResource::Sampler(&self.sampler),
+                        },
+                    ],
+                });
+
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Mipmap Pass"),
+                    color_attachments: &[
+                        Some(wgpu::RenderPassColorAttachment {
+                            view           : &views[level],
+                            resolve_target : None,
+                            ops            : wgpu::Operations {
+                                load  : wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                                store : true,
+                            },
+                        })
+                    ],
+                    depth_stencil_attachment: None,
+                });
+
+                render_pass.set_pipeline(&self.pipeline);
+                render_pass.set_bind_group(0, &bind_group, &[]);
+                render_pass.draw(0 .. 3, 0 .. 1);
+            }
+        });
+    }
+
+    pub fn mip_level_count(width: u32, height: u32) -> u32 {
+        return 32 - width.max(height).max(1).leading_zeros();
+    }
+}
\ No newline at end of file
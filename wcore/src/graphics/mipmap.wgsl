@@ -0,0 +1,24 @@
+struct VertexOutput {
+    @builtin(position) clip_pos : vec4<f32>,
+    @location(0)       uv       : vec2<f32>,
+}
+
+// Full screen triangle, no vertex buffer needed
+@vertex
+fn vertex_main(@builtin(vertex_index) index : u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VertexOutput;
+    out.clip_pos = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv       = uv;
+
+    return out;
+}
+
+@group(0) @binding(0) var t0 : texture_2d<f32>;
+@group(0) @binding(1) var s0 : sampler;
+
+@fragment
+fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSampleLevel(t0, s0, in.uv, 0.0);
+}
\ No newline at end of file
@@ -8,4 +8,15 @@ pub mod scene;
 pub mod primitive;
 pub mod uniform;
 pub mod camera;
-pub mod drawable;
\ No newline at end of file
+pub mod drawable;
+pub mod capture;
+pub mod overlay;
+pub mod text;
+pub mod sprite;
+pub mod atlas;
+pub mod sampler;
+pub mod mipmap;
+pub mod target;
+pub mod postprocess;
+pub mod pass;
+pub mod shape;
\ No newline at end of file
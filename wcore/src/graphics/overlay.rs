@@ -0,0 +1,193 @@
+use cgmath::{vec3, vec4, Quaternion, One, Vector4};
+use image::{DynamicImage, RgbaImage, Rgba};
+
+use crate::{input::{Input, VirtualKeyCode, ElementState}, timing::{FrameTimings, FrameStats, Phase}};
+
+use super::{
+    context::GraphicsContext, screen::Screen, scene::Scene2D, texture::Texture, bindable::Bindable,
+    pipeline::{model::ModelPipeline, Pipeline, shader::scene::SceneSlot}, camera::Projection, drawable::Drawable,
+    primitive::mesh::{instanced::InstancedMesh, data::{model::Model, vertex::Vertex}},
+    text::{TextRenderer, TextStyle, font::Font}, pass::RenderPassBuilder,
+};
+
+const GRAPH_X      : f32 = 10.0;
+const GRAPH_Y      : f32 = 10.0;
+const GRAPH_HEIGHT : f32 = 100.0;
+const BAR_WIDTH    : f32 = 2.0;
+
+/// Frame time that fills the whole graph height, two frames at 60 Hz.
+const GRAPH_MS     : f32 = 1000.0 / 30.0;
+
+/// Frame time graph drawn on top of the other screens, hidden until toggled with F3.
+/// Bars are stacked by phase: update, encode, submit and present, with the full frame time behind them.
+/// With a font set, the average, 99th percentile and worst time of each phase are listed below the graph.
+pub struct DebugOverlay {
+    pub visible : bool,
+
+    scene    : Scene2D,
+    pipeline : ModelPipeline,
+    mesh     : InstancedMesh<Model, Vertex>,
+    white    : Texture,
+    text     : Option<TextRenderer>,
+}
+
+impl DebugOverlay {
+    pub fn new(graphics: &GraphicsContext) -> Self {
+        let width = graphics.surface_configuration.width;
+        let height = graphics.surface_configuration.height;
+        let scene = Scene2D::new(&graphics.device, width, height, graphics.scale_factor);
+
+        let pipeline = ModelPipeline::new(graphics, &scene, false);
+        let (vertices, indices) = Vertex::indexed_rect(-0.5, 0.5);
+        let mesh = InstancedMesh::indexed(&graphics.device, vertices, indices, vec![]);
+
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 255])));
+        let white = Texture::from_image(graphics, &image, wgpu::FilterMode::Nearest, Some("white")).unwrap();
+
+        pipeline.update(&graphics.queue, &scene);
+
+        return Self {
+            visible: false,
+
+            scene,
+            pipeline,
+            mesh,
+            white,
+            text: None,
+        };
+    }
+
+    pub fn set_font(&mut self, graphics: &GraphicsContext, font: Font) {
+        let text = TextRenderer::new(graphics, &self.scene, vec![font]);
+        text.update(&graphics.queue, &self.scene);
+        self.text = Some(text);
+    }
+
+    fn summary(stats: &FrameStats) -> String {
+        let ms = |duration: instant::Duration| duration.as_secs_f32() * 1000.0;
+
+        let mut lines = vec![String::from("          avg    p99    max")];
+        for (name, phase) in [
+            ("update ", Phase::Update),
+            ("encode ", Phase::Encode),
+            ("submit ", Phase::Submit),
+            ("present", Phase::Present),
+            ("total  ", Phase::Total),
+        ] {
+            let summary = stats.summary(phase);
+            lines.push(format!("{} {:6.2} {:6.2} {:6.2}", name, ms(summary.avg), ms(summary.p99), ms(summary.max)));
+        }
+
+        return lines.join("\n");
+    }
+
+    fn rect(x: f32, y: f32, width: f32, height: f32, color: Vector4<f32>) -> Model {
+        return Model {
+            position : vec3(x + width / 2.0, y + height / 2.0, 0.0),
+            rotation : Quaternion::one(),
+            scale    : vec3(width, height, 1.0),
+            color,
+        };
+    }
+
+    fn bars(timings: &FrameTimings, x: f32, instances: &mut Vec<Model>) {
+        let to_height = |duration: instant::Duration| (duration.as_secs_f32() * 1000.0 / GRAPH_MS * GRAPH_HEIGHT).min(GRAPH_HEIGHT);
+        let bottom = GRAPH_Y + GRAPH_HEIGHT;
+
+        let total = to_height(timings.total);
+        instances.push(Self::rect(x, bottom - total, BAR_WIDTH, total, vec4(0.5, 0.5, 0.5, 0.6)));
+
+        let mut y = bottom;
+        for (duration, color) in [
+            (timings.update,  vec4(0.3, 0.5, 1.0, 1.0)),
+            (timings.encode,  vec4(0.3, 0.9, 0.3, 1.0)),
+            (timings.submit,  vec4(1.0, 0.7, 0.2, 1.0)),
+            (timings.present, vec4(0.8, 0.4, 1.0, 1.0)),
+        ] {
+            let height = to_height(duration).min(y - GRAPH_Y);
+            y -= height;
+            instances.push(Self::rect(x, y, BAR_WIDTH, height, color));
+        }
+    }
+}
+
+impl<State> Screen<State> for DebugOverlay {
+    fn render(&mut self, _state: &mut State, view: &wgpu::TextureView, graphics: &mut GraphicsContext) {
+        if !self.visible {
+            return;
+        }
+
+        let stats = &graphics.frame_stats;
+        let width = stats.capacity() as f32 * BAR_WIDTH;
+
+        let mut instances = vec![Self::rect(GRAPH_X, GRAPH_Y, width, GRAPH_HEIGHT, vec4(0.0, 0.0, 0.0, 0.5))];
+        for (i, timings) in stats.history().enumerate() {
+            Self::bars(timings, GRAPH_X + i as f32 * BAR_WIDTH, &mut instances);
+        }
+
+        // 60 Hz and 30 Hz frame times
+        for ms in [1000.0 / 60.0, 1000.0 / 30.0] {
+            let y = GRAPH_Y + GRAPH_HEIGHT - ms / GRAPH_MS * GRAPH_HEIGHT;
+            instances.push(Self::rect(GRAPH_X, y, width, 1.0, vec4(1.0, 0.2, 0.2, 0.8)));
+        }
+
+        self.mesh.instances = instances;
+        self.mesh.upload_instances(&graphics.device, &graphics.queue);
+
+        if let Some(text) = &mut self.text {
+            let style = TextStyle { size: 12.0, .. Default::default() };
+            text.queue(&Self::summary(stats), GRAPH_X, GRAPH_Y + GRAPH_HEIGHT + 4.0, &style);
+            text.prepare(graphics);
+        }
+
+        let target = graphics.attachment(view);
+        super::utils::submit(&graphics.queue, &graphics.device, |encoder| {
+            // Drawn over whatever the previous screens rendered
+            let mut render_pass = RenderPassBuilder::new(target).label("Debug Overlay Pass").begin(encoder);
+
+            self.white.bind(&mut render_pass, 1);
+            self.pipeline.attach(&mut render_pass);
+            self.mesh.draw(&mut render_pass);
+
+            if let Some(text) = &self.text {
+                text.draw(&mut render_pass);
+            }
+        });
+    }
+
+    fn resize(&mut self, _state: &mut State, width: u32, height: u32, graphics: &mut GraphicsContext) {
+        self.scene.projection.resize(width, height);
+        self.pipeline.update(&graphics.queue, &self.scene);
+
+        if let Some(text) = &self.text {
+            text.update(&graphics.queue, &self.scene);
+        }
+    }
+
+    fn rescale(&mut self, _state: &mut State, scale_factor: f64, graphics: &mut GraphicsContext) {
+        self.scene.projection.rescale(scale_factor);
+        self.pipeline.update(&graphics.queue, &self.scene);
+
+        if let Some(text) = &mut self.text {
+            text.rescale(scale_factor);
+            text.update(&graphics.queue, &self.scene);
+        }
+    }
+
+    fn recreate(&mut self, _state: &mut State, graphics: &mut GraphicsContext) {
+        let visible = self.visible;
+        let font = self.text.as_ref().map(|text| text.fonts()[0].clone());
+
+        *self = Self::new(graphics);
+        self.visible = visible;
+        if let Some(font) = font {
+            self.set_font(graphics, font);
+        }
+    }
+
+    fn input(&mut self, _state: &mut State, input: &Input, _graphics: &mut GraphicsContext) {
+        if let Input::KeyboardInput { key: Some(VirtualKeyCode::F3), state: ElementState::Pressed, .. } = input {
+            self.visible = !self.visible;
+        }
+    }
+}
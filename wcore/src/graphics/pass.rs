@@ -0,0 +1,96 @@
+use wgpu::TextureView;
+
+use super::utils::ColorTarget;
+
+/// Describes a render pass with a single color attachment.
+/// Color is loaded by default so passes draw over what is already there, `clear` starts from a color instead.
+pub struct RenderPassBuilder<'a> {
+    label       : Option<&'a str>,
+    target      : ColorTarget<'a>,
+    color_ops   : wgpu::Operations<wgpu::Color>,
+
+    depth       : Option<&'a TextureView>,
+    depth_ops   : Option<wgpu::Operations<f32>>,
+    stencil_ops : Option<wgpu::Operations<u32>>,
+}
+
+impl<'a> RenderPassBuilder<'a> {
+    pub fn new(target: impl Into<ColorTarget<'a>>) -> Self {
+        return Self {
+            label       : None,
+            target      : target.into(),
+            color_ops   : wgpu::Operations { load: wgpu::LoadOp::Load, store: true },
+
+            depth       : None,
+            depth_ops   : None,
+            stencil_ops : None,
+        };
+    }
+
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        return self;
+    }
+
+    pub fn clear(mut self, color: wgpu::Color) -> Self {
+        self.color_ops.load = wgpu::LoadOp::Clear(color);
+        return self;
+    }
+
+    pub fn load(mut self) -> Self {
+        self.color_ops.load = wgpu::LoadOp::Load;
+        return self;
+    }
+
+    /// Whether the color is written back, disable for passes that only write depth or stencil.
+    pub fn store(mut self, store: bool) -> Self {
+        self.color_ops.store = store;
+        return self;
+    }
+
+    /// Attaches a depth buffer, cleared to 1.0 and stored unless `depth_ops` says otherwise.
+    pub fn depth(mut self, view: &'a TextureView) -> Self {
+        self.depth = Some(view);
+        self.depth_ops.get_or_insert(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: true });
+        return self;
+    }
+
+    /// Like `depth`, skipped if `view` is `None`.
+    pub fn depth_if(self, view: Option<&'a TextureView>) -> Self {
+        return match view {
+            Some(view) => self.depth(view),
+            None       => self,
+        };
+    }
+
+    /// `None` leaves the depth aspect untouched, for read-only depth.
+    pub fn depth_ops(mut self, ops: Option<wgpu::Operations<f32>>) -> Self {
+        self.depth_ops = ops;
+        return self;
+    }
+
+    /// Only for depth formats with a stencil aspect.
+    pub fn stencil_ops(mut self, ops: Option<wgpu::Operations<u32>>) -> Self {
+        self.stencil_ops = ops;
+        return self;
+    }
+
+    pub fn begin(self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        return encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: self.label,
+            color_attachments: &[
+                // [[location(0)]] in the fragment shader
+                Some(wgpu::RenderPassColorAttachment {
+                    view           : self.target.view,
+                    resolve_target : self.target.resolve_target,
+                    ops            : self.color_ops,
+                })
+            ],
+            depth_stencil_attachment: self.depth.map(|view| wgpu::RenderPassDepthStencilAttachment {
+                view,
+                depth_ops   : self.depth_ops,
+                stencil_ops : self.stencil_ops,
+            }),
+        });
+    }
+}
\ No newline at end of file
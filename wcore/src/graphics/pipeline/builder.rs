@@ -0,0 +1,352 @@
+use std::{borrow::Cow, cell::RefCell, collections::{HashMap, hash_map::DefaultHasher}, hash::{Hash, Hasher}, ops::Range, sync::Arc};
+
+use crate::graphics::{context::GraphicsContext, target::RenderTarget};
+
+/// How the fragment output is combined with what the target already holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// Overwrites the target.
+    Replace,
+    #[default]
+    Alpha,
+    /// For colors already multiplied by their alpha, like the contents of render targets.
+    Premultiplied,
+    /// Adds the color weighted by its alpha, for glows and hit lighting.
+    Additive,
+    /// Multiplies the target by the color, for shadows and tinting.
+    /// Expects color premultiplied by alpha, so transparent parts leave the target untouched.
+    Multiply,
+}
+
+impl BlendMode {
+    pub fn state(self) -> Option<wgpu::BlendState> {
+        // Alpha is accumulated the usual way, so the result composites normally later on
+        let over = wgpu::BlendComponent::OVER;
+
+        return match self {
+            BlendMode::Replace       => None,
+            BlendMode::Alpha         => Some(wgpu::BlendState::ALPHA_BLENDING),
+            BlendMode::Premultiplied => Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+            BlendMode::Additive      => Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor : wgpu::BlendFactor::SrcAlpha,
+                    dst_factor : wgpu::BlendFactor::One,
+                    operation  : wgpu::BlendOperation::Add,
+                },
+                alpha: over,
+            }),
+            BlendMode::Multiply      => Some(wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor : wgpu::BlendFactor::Dst,
+                    dst_factor : wgpu::BlendFactor::OneMinusSrcAlpha,
+                    operation  : wgpu::BlendOperation::Add,
+                },
+                alpha: over,
+            }),
+        };
+    }
+}
+
+/// Describes a render pipeline by value, so it can be hashed, cached and rebuilt with a new shader.
+/// Starts out as an alpha blended triangle list with back-face culling, drawing into the surface format
+/// with the context's sample count, using `vertex_main` and `fragment_main` as entry points.
+#[derive(Debug, Clone)]
+pub struct PipelineBuilder {
+    label          : Option<String>,
+    shader         : Cow<'static, str>,
+    vertex_entry   : Cow<'static, str>,
+    fragment_entry : Cow<'static, str>,
+
+    groups         : Vec<&'static [wgpu::BindGroupLayoutEntry]>,
+    push_constants : Vec<wgpu::PushConstantRange>,
+    buffers        : Vec<wgpu::VertexBufferLayout<'static>>,
+
+    targets        : Vec<wgpu::ColorTargetState>,
+    primitive      : wgpu::PrimitiveState,
+    depth_stencil  : Option<wgpu::DepthStencilState>,
+    samples        : u32,
+}
+
+impl PipelineBuilder {
+    pub fn new(graphics: &GraphicsContext, shader: impl Into<Cow<'static, str>>) -> Self {
+        return Self {
+            label          : None,
+            shader         : shader.into(),
+            vertex_entry   : Cow::Borrowed("vertex_main"),
+            fragment_entry : Cow::Borrowed("fragment_main"),
+
+            groups         : vec![],
+            push_constants : vec![],
+            buffers        : vec![],
+
+            targets        : vec![wgpu::ColorTargetState {
+                format     : graphics.surface_configuration.format,
+                blend      : BlendMode::Alpha.state(),
+                write_mask : wgpu::ColorWrites::ALL,
+            }],
+            primitive      : wgpu::PrimitiveState {
+                topology   : wgpu::PrimitiveTopology::TriangleList,
+                front_face : wgpu::FrontFace::Ccw,
+                cull_mode  : Some(wgpu::Face::Back),
+                .. Default::default()
+            },
+            depth_stencil  : None,
+            samples        : graphics.sample_count,
+        };
+    }
+
+    /// Not part of the cache key, a shared pipeline keeps the label it was first built with.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        return self;
+    }
+
+    /// WGSL source, the module is created when the pipeline is built.
+    pub fn shader(mut self, shader: impl Into<Cow<'static, str>>) -> Self {
+        self.shader = shader.into();
+        return self;
+    }
+
+    pub fn entry_points(mut self, vertex: impl Into<Cow<'static, str>>, fragment: impl Into<Cow<'static, str>>) -> Self {
+        self.vertex_entry = vertex.into();
+        self.fragment_entry = fragment.into();
+        return self;
+    }
+
+    /// Adds the next bind group, usually `Bindable::layout_entries` of what will be bound there.
+    pub fn group(mut self, entries: &'static [wgpu::BindGroupLayoutEntry]) -> Self {
+        self.groups.push(entries);
+        return self;
+    }
+
+    /// Adds the next vertex buffer, usually `VertexLayout::describe`.
+    pub fn buffer(mut self, layout: wgpu::VertexBufferLayout<'static>) -> Self {
+        self.buffers.push(layout);
+        return self;
+    }
+
+    /// Requires `Features::PUSH_CONSTANTS`, which the context enables when the adapter has it.
+    pub fn push_constants(mut self, stages: wgpu::ShaderStages, range: Range<u32>) -> Self {
+        self.push_constants.push(wgpu::PushConstantRange { stages, range });
+        return self;
+    }
+
+    /// Format of the first color target, for drawing into something other than the surface.
+    pub fn format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.targets[0].format = format;
+        return self;
+    }
+
+    /// Blend mode of every color target.
+    pub fn blend(mut self, mode: BlendMode) -> Self {
+        for target in &mut self.targets {
+            target.blend = mode.state();
+        }
+
+        return self;
+    }
+
+    /// Adds a color target after the first, written by `@location(n)` outputs in the same order.
+    pub fn target(mut self, format: wgpu::TextureFormat, mode: BlendMode) -> Self {
+        self.targets.push(wgpu::ColorTargetState {
+            format,
+            blend      : mode.state(),
+            write_mask : wgpu::ColorWrites::ALL,
+        });
+
+        return self;
+    }
+
+    pub fn write_mask(mut self, mask: wgpu::ColorWrites) -> Self {
+        for target in &mut self.targets {
+            target.write_mask = mask;
+        }
+
+        return self;
+    }
+
+    /// `None` draws both sides, needed for shapes whose winding is not known up front.
+    pub fn cull(mut self, face: Option<wgpu::Face>) -> Self {
+        self.primitive.cull_mode = face;
+        return self;
+    }
+
+    /// Line topologies draw one pixel wide lines, for grid lines and debug shapes.
+    pub fn topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.primitive.topology = topology;
+        return self;
+    }
+
+    /// Indexed strips restart on the largest index of this format, which must match the index buffer.
+    pub fn strip_index_format(mut self, format: wgpu::IndexFormat) -> Self {
+        self.primitive.strip_index_format = Some(format);
+        return self;
+    }
+
+    /// Tests and writes depth with `Less` into the depth buffer the context manages.
+    pub fn depth(mut self, depth: bool) -> Self {
+        self.depth_stencil = depth.then(|| wgpu::DepthStencilState {
+            format              : RenderTarget::DEPTH_FORMAT,
+            depth_write_enabled : true,
+            depth_compare       : wgpu::CompareFunction::Less,
+            stencil             : wgpu::StencilState::default(),
+            bias                : wgpu::DepthBiasState::default(),
+        });
+
+        return self;
+    }
+
+    /// Enables depth if it is not already.
+    pub fn depth_compare(mut self, compare: wgpu::CompareFunction) -> Self {
+        self = self.ensure_depth();
+        self.depth_stencil.as_mut().unwrap().depth_compare = compare;
+        return self;
+    }
+
+    /// Disable writes for transparent geometry that should still be hidden behind opaque geometry.
+    pub fn depth_write(mut self, write: bool) -> Self {
+        self = self.ensure_depth();
+        self.depth_stencil.as_mut().unwrap().depth_write_enabled = write;
+        return self;
+    }
+
+    /// Needs a depth format with a stencil aspect like `Depth24PlusStencil8`, see `depth_stencil`.
+    pub fn stencil(mut self, stencil: wgpu::StencilState) -> Self {
+        self = self.ensure_depth();
+        self.depth_stencil.as_mut().unwrap().stencil = stencil;
+        return self;
+    }
+
+    /// Full control over the depth and stencil state, `None` disables both.
+    pub fn depth_stencil(mut self, state: Option<wgpu::DepthStencilState>) -> Self {
+        self.depth_stencil = state;
+        return self;
+    }
+
+    pub fn samples(mut self, samples: u32) -> Self {
+        self.samples = samples;
+        return self;
+    }
+
+    fn ensure_depth(self) -> Self {
+        return match self.depth_stencil {
+            Some(_) => self,
+            None    => self.depth(true),
+        };
+    }
+
+    /// Whether the pipeline tests against a depth buffer, see `Pipeline::depth`.
+    pub fn has_depth(&self) -> bool {
+        return self.depth_stencil.is_some();
+    }
+
+    /// Hash of everything that affects the pipeline, identical descriptions share one pipeline.
+    pub fn key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+
+        self.shader.hash(&mut hasher);
+        self.vertex_entry.hash(&mut hasher);
+        self.fragment_entry.hash(&mut hasher);
+        self.groups.hash(&mut hasher);
+        self.push_constants.hash(&mut hasher);
+        self.buffers.hash(&mut hasher);
+        self.targets.hash(&mut hasher);
+        self.primitive.hash(&mut hasher);
+        self.samples.hash(&mut hasher);
+
+        // Not Hash because of the float bias
+        if let Some(state) = &self.depth_stencil {
+            state.format.hash(&mut hasher);
+            state.depth_write_enabled.hash(&mut hasher);
+            state.depth_compare.hash(&mut hasher);
+            state.stencil.hash(&mut hasher);
+            state.bias.constant.hash(&mut hasher);
+            state.bias.slope_scale.to_bits().hash(&mut hasher);
+            state.bias.clamp.to_bits().hash(&mut hasher);
+        }
+
+        return hasher.finish();
+    }
+
+    /// Returns the cached pipeline if an identical one was built before.
+    pub fn build(&self, graphics: &GraphicsContext) -> Arc<wgpu::RenderPipeline> {
+        return graphics.pipelines.get(&graphics.device, self);
+    }
+
+    fn create(&self, device: &wgpu::Device) -> wgpu::RenderPipeline {
+        let label = self.label.as_deref();
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label,
+            source: wgpu::ShaderSource::Wgsl(self.shader.clone()),
+        });
+
+        let groups = self.groups.iter().map(|entries| device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries,
+        })).collect::<Vec<_>>();
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label                : Some("Render Pipeline Layout"),
+            push_constant_ranges : &self.push_constants,
+            bind_group_layouts   : &groups.iter().collect::<Vec<_>>(),
+        });
+
+        let targets = self.targets.iter().cloned().map(Some).collect::<Vec<_>>();
+        return device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label  : label.or(Some("Render Pipeline")),
+            layout : Some(&layout),
+
+            // Shaders
+            vertex: wgpu::VertexState {
+                module      : &shader,
+                entry_point : &self.vertex_entry,
+                buffers     : &self.buffers,
+            },
+
+            fragment: Some(wgpu::FragmentState {
+                module      : &shader,
+                entry_point : &self.fragment_entry,
+                targets     : &targets,
+            }),
+
+            // Other
+            primitive     : self.primitive,
+            depth_stencil : self.depth_stencil.clone(),
+
+            multiview   : None,
+            multisample : wgpu::MultisampleState {
+                count                     : self.samples,
+                mask                      : !0,
+                alpha_to_coverage_enabled : false,
+            },
+        });
+    }
+}
+
+/// Render pipelines by `PipelineBuilder::key`, identical descriptions share one pipeline.
+#[derive(Default)]
+pub struct PipelineCache {
+    pipelines: RefCell<HashMap<u64, Arc<wgpu::RenderPipeline>>>,
+}
+
+impl PipelineCache {
+    pub fn get(&self, device: &wgpu::Device, builder: &PipelineBuilder) -> Arc<wgpu::RenderPipeline> {
+        let mut pipelines = self.pipelines.borrow_mut();
+        let pipeline = pipelines.entry(builder.key()).or_insert_with(|| Arc::new(builder.create(device)));
+
+        return pipeline.clone();
+    }
+
+    /// Forgets a pipeline, for ones that turned out to be invalid or were replaced.
+    pub fn remove(&self, builder: &PipelineBuilder) {
+        self.pipelines.borrow_mut().remove(&builder.key());
+    }
+
+    pub fn len(&self) -> usize {
+        return self.pipelines.borrow().len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.pipelines.borrow().is_empty();
+    }
+}
\ No newline at end of file
@@ -1,6 +1,13 @@
 pub mod model;
+pub mod text;
+pub mod sprite;
+pub mod shape;
 pub mod shader;
+pub mod builder;
 
 pub trait Pipeline {
     fn attach<'a, 'b: 'a>(&'b self, render_pass: &mut wgpu::RenderPass<'a>);
+
+    /// Whether the pipeline tests against a depth buffer, render helpers attach one for it.
+    fn depth(&self) -> bool { false }
 }
\ No newline at end of file
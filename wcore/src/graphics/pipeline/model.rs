@@ -1,46 +1,40 @@
-use wgpu::include_wgsl;
+use std::sync::Arc;
 
-use crate::{graphics::{texture::Texture, scene::Scene, primitive::mesh::data::{vertex::Vertex, model::ModelRaw}, uniform::Uniform, bindable::Bindable, utils}};
+use crate::{graphics::{context::GraphicsContext, texture::Texture, scene::Scene, primitive::mesh::{data::{vertex::Vertex, model::ModelRaw}, layout::VertexLayout}, uniform::Uniform, bindable::Bindable}, shader_source};
 
-use super::{shader::scene::SceneSlot, Pipeline};
+use super::{shader::{scene::SceneSlot, reload::ReloadablePipeline}, builder::PipelineBuilder, Pipeline};
 
 pub struct ModelPipeline {
-    pipeline: wgpu::RenderPipeline,
+    pipeline: Arc<ReloadablePipeline>,
     
     scene_uniform: Uniform<[[f32; 4]; 4]>
 }
 
 impl Pipeline for ModelPipeline {
     fn attach<'a, 'b: 'a>(&'b self, render_pass: &mut wgpu::RenderPass<'a>) {
-        render_pass.set_pipeline(&self.pipeline);
+        // SAFETY: render passes end within the frame and shaders are only reloaded between frames
+        render_pass.set_pipeline(unsafe { self.pipeline.get() });
         self.scene_uniform.bind(render_pass, 0);
     }
+
+    fn depth(&self) -> bool {
+        return self.pipeline.has_depth();
+    }
 }
 
 impl ModelPipeline {
-    pub fn new(device: &wgpu::Device, surface_configuration: &wgpu::SurfaceConfiguration, scene: &impl Scene, depth: bool) -> Self {
-        let shader = device.create_shader_module(include_wgsl!("model.wgsl"));
-        
-        let bind_layout = &[
-            scene.layout(),
-            &Texture::default_layout(device),
-        ];
-
-        let buffer_layout = &[
-            Vertex::describe(),
-            ModelRaw::describe(),
-        ];
-
-        let pipeline = utils::pipeline(
-            device,
-            &shader,
-            surface_configuration,
-            bind_layout,
-            buffer_layout,
-            depth
-        );
-
-        let scene_uniform = Uniform::new(device);
+    /// Draws with the sample count of the context, into `GraphicsContext::attachment` or a `RenderTarget`.
+    pub fn new(graphics: &GraphicsContext, scene: &impl Scene, depth: bool) -> Self {
+        let source = shader_source!("model.wgsl");
+        let builder = PipelineBuilder::new(graphics, source.embedded)
+            .group(scene.layout_entries())
+            .group(Texture::LAYOUT_ENTRIES)
+            .buffer(Vertex::describe())
+            .buffer(ModelRaw::describe())
+            .depth(depth);
+
+        let pipeline = ReloadablePipeline::new(graphics, source, builder);
+        let scene_uniform = Uniform::new(&graphics.device);
 
         return Self {
             pipeline,
@@ -44,10 +44,11 @@ fn vertex_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
 // Fragment
 @group(1) @binding(0) var t0 : texture_2d<f32>;
 @group(1) @binding(1) var s0 : sampler;
+@group(1) @binding(2) var<uniform> p0 : vec4<f32>; // x: LOD bias
 
 @fragment
 fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let texture = textureSample(t0, s0, in.uv);
+    let texture = textureSampleBias(t0, s0, in.uv, p0.x);
 
     return texture * in.color;
 }
\ No newline at end of file
@@ -1 +1,2 @@
-pub mod scene;
\ No newline at end of file
+pub mod scene;
+pub mod reload;
\ No newline at end of file
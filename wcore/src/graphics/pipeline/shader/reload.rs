@@ -0,0 +1,244 @@
+use std::{fmt, fs, path::{Path, PathBuf}, sync::{Arc, Mutex, Weak}, time::SystemTime};
+
+use log::{error, info};
+
+use crate::graphics::{context::GraphicsContext, pipeline::builder::PipelineBuilder};
+
+/// Embeds a WGSL file relative to the current file like `include_wgsl!`, remembering where it lives on disk
+/// so `ReloadablePipeline` can pick up edits in debug builds.
+#[macro_export]
+macro_rules! shader_source {
+    ($name: literal) => {
+        $crate::graphics::pipeline::shader::reload::ShaderSource {
+            manifest : env!("CARGO_MANIFEST_DIR"),
+            file     : file!(),
+            name     : $name,
+            embedded : include_str!($name),
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ShaderSource {
+    pub manifest : &'static str,
+    /// Rust file that embedded the shader, the shader is looked up next to it.
+    pub file     : &'static str,
+    pub name     : &'static str,
+    pub embedded : &'static str,
+}
+
+impl ShaderSource {
+    pub fn path(&self) -> PathBuf {
+        // `file!()` is relative to the manifest unless the crate was built from elsewhere, joining keeps absolute paths
+        return Path::new(self.manifest).join(self.file).with_file_name(self.name);
+    }
+}
+
+/// A shader that failed to compile, with the 1-based line and column if the error points into the source.
+#[derive(Debug, Clone)]
+pub struct ShaderError {
+    pub path     : PathBuf,
+    pub location : Option<(u32, u32)>,
+    pub message  : String,
+}
+
+impl ShaderError {
+    fn new(path: &Path, location: Option<naga::SourceLocation>, message: String) -> Self {
+        return Self {
+            path: path.to_owned(),
+            location: location.map(|location| (location.line_number, location.line_position)),
+            message,
+        };
+    }
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self.location {
+            Some((line, column)) => write!(f, "{}:{}:{}: {}", self.path.display(), line, column, self.message),
+            None                 => write!(f, "{}: {}", self.path.display(), self.message),
+        };
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
+/// Parses and validates WGSL with naga, so broken shaders are reported instead of reaching wgpu.
+pub fn validate(path: &Path, source: &str) -> Result<(), ShaderError> {
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|e| ShaderError::new(path, e.location(source), e.message().to_owned()))?;
+
+    let mut validator = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::all());
+    if let Err(e) = validator.validate(&module) {
+        let location = e.location(source);
+
+        // Validation errors nest, the innermost one says what is actually wrong
+        let mut message = e.as_inner().to_string();
+        let mut cause = std::error::Error::source(e.as_inner());
+        while let Some(inner) = cause {
+            message = format!("{}: {}", message, inner);
+            cause = inner.source();
+        }
+
+        return Err(ShaderError::new(path, location, message));
+    }
+
+    return Ok(());
+}
+
+/// Render pipeline rebuilt from its shader file whenever `ShaderWatcher::poll` sees it change.
+/// Only debug builds read from disk, release builds always use the embedded source.
+pub struct ReloadablePipeline {
+    source : ShaderSource,
+    state  : Mutex<State>,
+}
+
+struct State {
+    /// Description of `pipeline`, the shader is replaced on every reload.
+    builder  : PipelineBuilder,
+    pipeline : Arc<wgpu::RenderPipeline>,
+    /// Modification time of the last version tried, so a broken file is only reported once.
+    modified : Option<SystemTime>,
+}
+
+impl ReloadablePipeline {
+    /// Builds the pipeline and registers it with the context's `ShaderWatcher` in debug builds.
+    /// The shader of `builder` is replaced by the source, everything else is kept across reloads.
+    pub fn new(graphics: &GraphicsContext, source: ShaderSource, builder: PipelineBuilder) -> Arc<Self> {
+        let builder = builder.label(source.name);
+        let mut modified = None;
+        let mut built = None;
+
+        // Prefer the file on disk, it may have changed since the last build
+        if cfg!(debug_assertions) {
+            let path = source.path();
+            if let Ok((code, time)) = Self::modified(&path).and_then(|time| Ok((fs::read_to_string(&path)?, time))) {
+                modified = Some(time);
+                match Self::build(graphics, builder.clone(), code, &path) {
+                    Ok(disk) => built = Some(disk),
+                    Err(e)   => error!("{e}, using the embedded shader"),
+                }
+            }
+        }
+
+        let (builder, pipeline) = built.unwrap_or_else(|| {
+            let builder = builder.shader(source.embedded);
+            let pipeline = builder.build(graphics);
+            return (builder, pipeline);
+        });
+
+        let pipeline = Arc::new(Self {
+            state: Mutex::new(State { builder, pipeline, modified }),
+            source,
+        });
+
+        if cfg!(debug_assertions) {
+            graphics.shaders.watch(&pipeline);
+        }
+
+        return pipeline;
+    }
+
+    /// The most recently built pipeline.
+    ///
+    /// # Safety
+    /// A reload drops the pipeline it replaces, so the reference must not be used after the next `ShaderWatcher::poll`.
+    /// Attaching it to a render pass that ends within the frame is fine, the engine only polls between frames.
+    pub unsafe fn get(&self) -> &wgpu::RenderPipeline {
+        let pipeline = Arc::as_ptr(&self.state.lock().unwrap().pipeline);
+
+        // SAFETY: the pipeline stays in `state` until a reload, which the caller does not let the reference outlive
+        return unsafe { &*pipeline };
+    }
+
+    /// Rebuilds the pipeline if its shader file changed. The current pipeline is kept if the new one fails.
+    pub fn reload(&self, graphics: &GraphicsContext) -> Result<bool, ShaderError> {
+        let path = self.source.path();
+        let mut state = self.state.lock().unwrap();
+
+        // Only the metadata is read every frame, the file itself only when it changed
+        let Ok(time) = Self::modified(&path) else {
+            return Ok(false);
+        };
+
+        if state.modified == Some(time) {
+            return Ok(false);
+        }
+
+        let Ok(code) = fs::read_to_string(&path) else {
+            return Ok(false);
+        };
+
+        state.modified = Some(time);
+        let (builder, pipeline) = Self::build(graphics, state.builder.clone(), code, &path)?;
+
+        // The cache would otherwise keep every version alive
+        graphics.pipelines.remove(&state.builder);
+        state.pipeline = pipeline;
+        state.builder = builder;
+
+        return Ok(true);
+    }
+
+    /// Validates the shader with naga and builds it inside an error scope, so a broken file is reported instead of
+    /// panicking in wgpu. Naga accepting the shader does not mean it fits the pipeline, entry points or vertex inputs may not match.
+    fn build(graphics: &GraphicsContext, builder: PipelineBuilder, code: String, path: &Path) -> Result<(PipelineBuilder, Arc<wgpu::RenderPipeline>), ShaderError> {
+        validate(path, &code)?;
+        let builder = builder.shader(code);
+
+        graphics.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = builder.build(graphics);
+        if let Some(e) = pollster::block_on(graphics.device.pop_error_scope()) {
+            graphics.pipelines.remove(&builder);
+            return Err(ShaderError::new(path, None, e.to_string()));
+        }
+
+        return Ok((builder, pipeline));
+    }
+
+    fn modified(path: &Path) -> std::io::Result<SystemTime> {
+        return fs::metadata(path)?.modified();
+    }
+
+    /// Whether the pipeline tests against a depth buffer, see `Pipeline::depth`.
+    pub fn has_depth(&self) -> bool {
+        return self.state.lock().unwrap().builder.has_depth();
+    }
+}
+
+/// Pipelines to reload when their shaders change, polled once per frame.
+#[derive(Default)]
+pub struct ShaderWatcher {
+    pipelines: Mutex<Vec<Weak<ReloadablePipeline>>>,
+}
+
+impl ShaderWatcher {
+    pub fn watch(&self, pipeline: &Arc<ReloadablePipeline>) {
+        self.pipelines.lock().unwrap().push(Arc::downgrade(pipeline));
+    }
+
+    /// Reloads every pipeline whose shader file changed and logs the result. Does nothing in release builds.
+    /// Replaced pipelines are dropped, see `ReloadablePipeline::get`.
+    pub fn poll(&self, graphics: &GraphicsContext) -> Vec<ShaderError> {
+        if !cfg!(debug_assertions) {
+            return vec![];
+        }
+
+        let mut errors = vec![];
+        let mut pipelines = self.pipelines.lock().unwrap();
+        pipelines.retain(|pipeline| pipeline.strong_count() > 0);
+
+        for pipeline in pipelines.iter().filter_map(Weak::upgrade) {
+            match pipeline.reload(graphics) {
+                Ok(true)  => info!("Reloaded {}", pipeline.source.path().display()),
+                Ok(false) => {},
+                Err(e)    => {
+                    error!("{e}");
+                    errors.push(e);
+                },
+            }
+        }
+
+        return errors;
+    }
+}
\ No newline at end of file
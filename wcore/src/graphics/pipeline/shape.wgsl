@@ -0,0 +1,31 @@
+//Vertex
+struct CameraUniform {
+    view_proj : mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> camera : CameraUniform;
+
+struct VertexInput {
+    @location(0) pos   : vec2<f32>,
+    @location(1) color : vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_pos : vec4<f32>,
+    @location(0)       color    : vec4<f32>,
+}
+
+@vertex
+fn vertex_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_pos = camera.view_proj * vec4<f32>(in.pos, 0.0, 1.0);
+    out.color    = in.color;
+
+    return out;
+}
+
+// Fragment
+@fragment
+fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use crate::{graphics::{context::GraphicsContext, texture::Texture, scene::Scene, primitive::mesh::{data::{vertex::Vertex, glyph::GlyphRaw}, layout::VertexLayout}, uniform::Uniform, bindable::Bindable}, shader_source};
+
+use super::{shader::{scene::SceneSlot, reload::ReloadablePipeline}, builder::PipelineBuilder, Pipeline};
+
+pub struct TextPipeline {
+    pipeline: Arc<ReloadablePipeline>,
+
+    scene_uniform: Uniform<[[f32; 4]; 4]>
+}
+
+impl Pipeline for TextPipeline {
+    fn attach<'a, 'b: 'a>(&'b self, render_pass: &mut wgpu::RenderPass<'a>) {
+        // SAFETY: render passes end within the frame and shaders are only reloaded between frames
+        render_pass.set_pipeline(unsafe { self.pipeline.get() });
+        self.scene_uniform.bind(render_pass, 0);
+    }
+
+    fn depth(&self) -> bool {
+        return self.pipeline.has_depth();
+    }
+}
+
+impl TextPipeline {
+    pub fn new(graphics: &GraphicsContext, scene: &impl Scene, depth: bool) -> Self {
+        let source = shader_source!("text.wgsl");
+        let builder = PipelineBuilder::new(graphics, source.embedded)
+            .group(scene.layout_entries())
+            .group(Texture::LAYOUT_ENTRIES)
+            .buffer(Vertex::describe())
+            .buffer(GlyphRaw::describe())
+            .depth(depth);
+
+        let pipeline = ReloadablePipeline::new(graphics, source, builder);
+        let scene_uniform = Uniform::new(&graphics.device);
+
+        return Self {
+            pipeline,
+
+            scene_uniform,
+        };
+    }
+}
+
+unsafe impl SceneSlot for TextPipeline {
+    fn update(&self, queue: &wgpu::Queue, scene: &impl Scene) {
+        self.scene_uniform.update(queue, &scene.apply().into());
+    }
+}
\ No newline at end of file
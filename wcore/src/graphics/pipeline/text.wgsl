@@ -0,0 +1,48 @@
+//Vertex
+struct CameraUniform {
+    view_proj : mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> camera : CameraUniform;
+
+struct InstanceInput {
+    @location(5) rect  : vec4<f32>,
+    @location(6) uv    : vec4<f32>,
+    @location(7) color : vec4<f32>,
+}
+
+struct VertexInput {
+    @location(0) pos : vec3<f32>,
+    @location(1) uv  : vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_pos : vec4<f32>,
+    @location(0)       uv       : vec2<f32>,
+    @location(1)       color    : vec4<f32>,
+}
+
+@vertex
+fn vertex_main(in: VertexInput, instance: InstanceInput) -> VertexOutput {
+    // The mesh is a unit quad, stretched over the glyph rectangle
+    let pos = instance.rect.xy + in.pos.xy * instance.rect.zw;
+
+    var out: VertexOutput;
+    out.clip_pos = camera.view_proj * vec4<f32>(pos, in.pos.z, 1.0);
+    out.uv       = mix(instance.uv.xy, instance.uv.zw, in.uv);
+    out.color    = instance.color;
+
+    return out;
+}
+
+// Fragment
+@group(1) @binding(0) var t0 : texture_2d<f32>;
+@group(1) @binding(1) var s0 : sampler;
+@group(1) @binding(2) var<uniform> p0 : vec4<f32>; // x: LOD bias
+
+@fragment
+fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coverage = textureSampleBias(t0, s0, in.uv, p0.x).a;
+
+    return vec4<f32>(in.color.rgb, in.color.a * coverage);
+}
\ No newline at end of file
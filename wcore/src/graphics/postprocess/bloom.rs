@@ -0,0 +1,73 @@
+use crate::graphics::{context::GraphicsContext, texture::Texture, utils::ColorTarget, target::RenderTarget, uniform::Uniform, bindable::Bindable};
+
+use super::{Effect, FullscreenPass, fullscreen_shader, blur::Blur};
+
+/// Glow around bright areas: bright parts are extracted at half resolution, blurred and added back.
+pub struct Bloom {
+    /// Brightness above which pixels start to glow, 0 to 1.
+    pub threshold : f32,
+    /// Width of the soft transition below the threshold.
+    pub knee      : f32,
+    pub intensity : f32,
+    /// Blur radius in half resolution pixels.
+    pub radius    : f32,
+
+    bright            : FullscreenPass,
+    bright_uniform    : Uniform<[f32; 4]>,
+    composite         : FullscreenPass,
+    composite_uniform : Uniform<[f32; 4]>,
+
+    blur    : Blur,
+    targets : [RenderTarget; 2],
+}
+
+impl Bloom {
+    pub fn new(graphics: &GraphicsContext) -> Self {
+        let targets = [
+            RenderTarget::window_sized(graphics, 0.5, false, wgpu::FilterMode::Linear, Some("bloom_0")).single_sampled(graphics),
+            RenderTarget::window_sized(graphics, 0.5, false, wgpu::FilterMode::Linear, Some("bloom_1")).single_sampled(graphics),
+        ];
+
+        let texture_layout = targets[0].texture.layout();
+        let bright_uniform = Uniform::new(&graphics.device);
+        let composite_uniform = Uniform::new(&graphics.device);
+
+        let bright = FullscreenPass::new(graphics, fullscreen_shader!("bright.wgsl"), &[texture_layout, bright_uniform.layout()]);
+        let composite = FullscreenPass::new(graphics, fullscreen_shader!("composite.wgsl"), &[texture_layout, composite_uniform.layout(), texture_layout]);
+
+        return Self {
+            threshold : 0.8,
+            knee      : 0.2,
+            intensity : 1.0,
+            radius    : 12.0,
+
+            bright,
+            bright_uniform,
+            composite,
+            composite_uniform,
+
+            blur: Blur::with_scale(graphics, 12.0, 0.5),
+            targets,
+        };
+    }
+}
+
+impl Effect for Bloom {
+    fn apply(&mut self, graphics: &GraphicsContext, encoder: &mut wgpu::CommandEncoder, input: &Texture, output: ColorTarget) {
+        self.bright_uniform.update(&graphics.queue, &[self.threshold, self.knee.max(0.0), 0.0, 0.0]);
+        self.composite_uniform.update(&graphics.queue, &[self.intensity, 0.0, 0.0, 0.0]);
+        self.blur.radius = self.radius;
+
+        self.bright.draw(encoder, self.targets[0].view(), &[input.group(), self.bright_uniform.group()]);
+        self.blur.apply(graphics, encoder, &self.targets[0].texture, self.targets[1].view().into());
+        self.composite.draw(encoder, output, &[input.group(), self.composite_uniform.group(), self.targets[1].texture.group()]);
+    }
+
+    fn resize(&mut self, graphics: &GraphicsContext) {
+        for target in &mut self.targets {
+            target.resize_to_window(graphics);
+        }
+
+        self.blur.resize(graphics);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+use crate::graphics::{context::GraphicsContext, texture::Texture, utils::ColorTarget, target::RenderTarget, uniform::Uniform, bindable::Bindable};
+
+use super::{Effect, FullscreenPass, fullscreen_shader};
+
+/// Separable Gaussian blur, a horizontal pass into an intermediate target followed by a vertical one.
+pub struct Blur {
+    /// In pixels of the blur's resolution, 0 passes the input through.
+    pub radius : f32,
+
+    pass       : FullscreenPass,
+    horizontal : Uniform<[f32; 4]>,
+    vertical   : Uniform<[f32; 4]>,
+    target     : RenderTarget,
+}
+
+impl Blur {
+    /// Taps per side are capped, larger radii should blur at a lower `scale`.
+    const MAX_TAPS: f32 = 64.0;
+
+    pub fn new(graphics: &GraphicsContext, radius: f32) -> Self {
+        return Self::with_scale(graphics, radius, 1.0);
+    }
+
+    /// Blurs at a fraction of the window resolution, cheaper for wide radii.
+    pub fn with_scale(graphics: &GraphicsContext, radius: f32, scale: f32) -> Self {
+        let target = RenderTarget::window_sized(graphics, scale, false, wgpu::FilterMode::Linear, Some("blur")).single_sampled(graphics);
+        let horizontal = Uniform::new(&graphics.device);
+        let vertical = Uniform::new(&graphics.device);
+
+        let pass = FullscreenPass::new(graphics, fullscreen_shader!("blur.wgsl"), &[target.texture.layout(), horizontal.layout()]);
+
+        return Self { radius, pass, horizontal, vertical, target };
+    }
+}
+
+impl Effect for Blur {
+    fn apply(&mut self, graphics: &GraphicsContext, encoder: &mut wgpu::CommandEncoder, input: &Texture, output: ColorTarget) {
+        let radius = self.radius.max(0.0);
+        let sigma = (radius / 3.0).max(0.0001);
+        let taps = radius.ceil().min(Self::MAX_TAPS);
+
+        self.horizontal.update(&graphics.queue, &[1.0, 0.0, sigma, taps]);
+        self.vertical.update(&graphics.queue, &[0.0, 1.0, sigma, taps]);
+
+        self.pass.draw(encoder, self.target.view(), &[input.group(), self.horizontal.group()]);
+        self.pass.draw(encoder, output, &[self.target.texture.group(), self.vertical.group()]);
+    }
+
+    fn resize(&mut self, graphics: &GraphicsContext) {
+        self.target.resize_to_window(graphics);
+    }
+}
\ No newline at end of file
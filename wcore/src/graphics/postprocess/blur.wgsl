@@ -0,0 +1,27 @@
+struct BlurParams {
+    direction : vec2<f32>,
+    sigma     : f32,
+    taps      : f32,
+}
+
+@group(1) @binding(0) var<uniform> params : BlurParams;
+
+// One direction of a separable Gaussian
+@fragment
+fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = params.direction / vec2<f32>(textureDimensions(t0));
+    let taps  = i32(params.taps);
+
+    var color = textureSampleLevel(t0, s0, in.uv, 0.0);
+    var total = 1.0;
+    for (var i = 1; i <= taps; i++) {
+        let offset = f32(i);
+        let weight = exp(-(offset * offset) / (2.0 * params.sigma * params.sigma));
+
+        color += textureSampleLevel(t0, s0, in.uv + texel * offset, 0.0) * weight;
+        color += textureSampleLevel(t0, s0, in.uv - texel * offset, 0.0) * weight;
+        total += 2.0 * weight;
+    }
+
+    return color / total;
+}
\ No newline at end of file
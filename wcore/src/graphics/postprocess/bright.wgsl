@@ -0,0 +1,21 @@
+struct BrightParams {
+    threshold : f32,
+    knee      : f32,
+    _0        : f32,
+    _1        : f32,
+}
+
+@group(1) @binding(0) var<uniform> params : BrightParams;
+
+// Keeps what is brighter than the threshold, with a soft knee below it
+@fragment
+fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color      = textureSampleLevel(t0, s0, in.uv, 0.0);
+    let brightness = max(color.r, max(color.g, color.b));
+
+    let soft   = clamp(brightness - params.threshold + params.knee, 0.0, 2.0 * params.knee);
+    let curve  = soft * soft / (4.0 * params.knee + 0.0001);
+    let factor = max(curve, brightness - params.threshold) / max(brightness, 0.0001);
+
+    return vec4<f32>(color.rgb * factor, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,42 @@
+use crate::graphics::{context::GraphicsContext, texture::Texture, utils::ColorTarget, uniform::Uniform, bindable::Bindable};
+
+use super::{Effect, FullscreenPass, fullscreen_shader};
+
+/// Background dim, saturation, contrast and brightness in one pass.
+pub struct ColorAdjust {
+    /// 0 keeps the colors, 1 is black.
+    pub dim        : f32,
+    /// 0 is grayscale, 1 keeps the colors, above 1 oversaturates.
+    pub saturation : f32,
+    /// 1 keeps the colors.
+    pub contrast   : f32,
+    /// Added to every channel, 0 keeps the colors.
+    pub brightness : f32,
+
+    pass    : FullscreenPass,
+    uniform : Uniform<[f32; 4]>,
+}
+
+impl ColorAdjust {
+    pub fn new(graphics: &GraphicsContext) -> Self {
+        let uniform = Uniform::new(&graphics.device);
+        let pass = FullscreenPass::new(graphics, fullscreen_shader!("color.wgsl"), &[&Texture::default_layout(&graphics.device), uniform.layout()]);
+
+        return Self {
+            dim        : 0.0,
+            saturation : 1.0,
+            contrast   : 1.0,
+            brightness : 0.0,
+
+            pass,
+            uniform,
+        };
+    }
+}
+
+impl Effect for ColorAdjust {
+    fn apply(&mut self, graphics: &GraphicsContext, encoder: &mut wgpu::CommandEncoder, input: &Texture, output: ColorTarget) {
+        self.uniform.update(&graphics.queue, &[self.dim.clamp(0.0, 1.0), self.saturation, self.contrast, self.brightness]);
+        self.pass.draw(encoder, output, &[input.group(), self.uniform.group()]);
+    }
+}
\ No newline at end of file
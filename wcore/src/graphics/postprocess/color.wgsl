@@ -0,0 +1,20 @@
+struct ColorParams {
+    dim        : f32,
+    saturation : f32,
+    contrast   : f32,
+    brightness : f32,
+}
+
+@group(1) @binding(0) var<uniform> params : ColorParams;
+
+@fragment
+fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSampleLevel(t0, s0, in.uv, 0.0);
+    let luma  = dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+
+    var rgb = mix(vec3<f32>(luma), color.rgb, params.saturation);
+    rgb = (rgb - 0.5) * params.contrast + 0.5 + params.brightness;
+    rgb = max(rgb, vec3<f32>(0.0)) * (1.0 - params.dim);
+
+    return vec4<f32>(rgb, color.a);
+}
\ No newline at end of file
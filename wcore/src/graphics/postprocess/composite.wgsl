@@ -0,0 +1,19 @@
+struct CompositeParams {
+    intensity : f32,
+    _0        : f32,
+    _1        : f32,
+    _2        : f32,
+}
+
+@group(1) @binding(0) var<uniform> params : CompositeParams;
+
+@group(2) @binding(0) var t1 : texture_2d<f32>;
+@group(2) @binding(1) var s1 : sampler;
+
+@fragment
+fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base  = textureSampleLevel(t0, s0, in.uv, 0.0);
+    let bloom = textureSampleLevel(t1, s1, in.uv, 0.0);
+
+    return vec4<f32>(base.rgb + bloom.rgb * params.intensity, base.a);
+}
\ No newline at end of file
@@ -0,0 +1,4 @@
+@fragment
+fn fragment_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSampleLevel(t0, s0, in.uv, 0.0);
+}
\ No newline at end of file
@@ -0,0 +1,20 @@
+struct VertexOutput {
+    @builtin(position) clip_pos : vec4<f32>,
+    @location(0)       uv       : vec2<f32>,
+}
+
+// Full screen triangle, no vertex buffer needed
+@vertex
+fn vertex_main(@builtin(vertex_index) index : u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VertexOutput;
+    out.clip_pos = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv       = uv;
+
+    return out;
+}
+
+// Input
+@group(0) @binding(0) var t0 : texture_2d<f32>;
+@group(0) @binding(1) var s0 : sampler;
@@ -0,0 +1,199 @@
+pub mod blur;
+pub mod bloom;
+pub mod color;
+
+use std::any::Any;
+
+use super::{bindable::Bindable, context::GraphicsContext, texture::Texture, target::RenderTarget, pipeline::builder::BlendMode, utils::{self, ColorTarget}};
+
+/// Compiles a fragment shader together with the shared full screen vertex stage.
+macro_rules! fullscreen_shader {
+    ($path: literal) => {
+        wgpu::ShaderModuleDescriptor {
+            label  : Some($path),
+            source : wgpu::ShaderSource::Wgsl(std::borrow::Cow::Borrowed(concat!(include_str!("fullscreen.wgsl"), include_str!($path)))),
+        }
+    };
+}
+
+pub(crate) use fullscreen_shader;
+
+/// A single post-processing step, reading one texture and writing into a target with the surface format.
+/// Parameters are uploaded on every `apply`, so they can be changed each frame to animate them.
+pub trait Effect: Any {
+    fn apply(&mut self, graphics: &GraphicsContext, encoder: &mut wgpu::CommandEncoder, input: &Texture, output: ColorTarget);
+
+    /// Called with the window, effects with their own targets resize them here.
+    #[allow(unused_variables)]
+    fn resize(&mut self, graphics: &GraphicsContext) {}
+
+    /// Disabled effects are skipped without an extra pass.
+    fn enabled(&self) -> bool { true }
+}
+
+/// Draws a full screen triangle with a fragment shader, input texture at group 0.
+pub struct FullscreenPass {
+    pipeline     : wgpu::RenderPipeline,
+    /// For multisampled targets like the frame with MSAA, resolved at the end of the pass.
+    multisampled : Option<wgpu::RenderPipeline>,
+    blend        : BlendMode,
+}
+
+impl FullscreenPass {
+    /// Replaces everything in the target, for intermediate targets.
+    pub fn new(graphics: &GraphicsContext, shader: wgpu::ShaderModuleDescriptor, groups: &[&wgpu::BindGroupLayout]) -> Self {
+        return Self::blended(graphics, shader, groups, BlendMode::Replace);
+    }
+
+    /// Blends over what the target already holds, for drawing into the frame after other screens.
+    pub fn blended(graphics: &GraphicsContext, shader: wgpu::ShaderModuleDescriptor, groups: &[&wgpu::BindGroupLayout], blend: BlendMode) -> Self {
+        let device = &graphics.device;
+        let shader = device.create_shader_module(shader);
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label                : Some("Fullscreen Pipeline Layout"),
+            bind_group_layouts   : groups,
+            push_constant_ranges : &[],
+        });
+
+        let pipeline = Self::make_pipeline(graphics, &shader, &layout, blend, 1);
+        let multisampled = (graphics.sample_count > 1).then(|| Self::make_pipeline(graphics, &shader, &layout, blend, graphics.sample_count));
+
+        return Self { pipeline, multisampled, blend };
+    }
+
+    /// Copies the texture bound at group 0 into the target.
+    pub fn copy(graphics: &GraphicsContext) -> Self {
+        return Self::new(graphics, fullscreen_shader!("copy.wgsl"), &[&Texture::default_layout(&graphics.device)]);
+    }
+
+    fn make_pipeline(graphics: &GraphicsContext, shader: &wgpu::ShaderModule, layout: &wgpu::PipelineLayout, blend: BlendMode, samples: u32) -> wgpu::RenderPipeline {
+        return graphics.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label  : Some("Fullscreen Pipeline"),
+            layout : Some(layout),
+
+            vertex: wgpu::VertexState {
+                module      : shader,
+                entry_point : "vertex_main",
+                buffers     : &[],
+            },
+
+            fragment: Some(wgpu::FragmentState {
+                module      : shader,
+                entry_point : "fragment_main",
+                targets     : &[Some(wgpu::ColorTargetState {
+                    format     : graphics.surface_configuration.format,
+                    blend      : blend.state(),
+                    write_mask : wgpu::ColorWrites::ALL,
+                })],
+            }),
+
+            primitive     : wgpu::PrimitiveState::default(),
+            depth_stencil : None,
+            multisample   : wgpu::MultisampleState { count: samples, .. Default::default() },
+            multiview     : None,
+        });
+    }
+
+    pub fn draw<'a>(&self, encoder: &mut wgpu::CommandEncoder, output: impl Into<ColorTarget<'a>>, groups: &[&wgpu::BindGroup]) {
+        let output = output.into();
+        let pipeline = match (&self.multisampled, output.resolve_target) {
+            (Some(multisampled), Some(_)) => multisampled,
+            _                             => &self.pipeline,
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Post Process Pass"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view           : output.view,
+                    resolve_target : output.resolve_target,
+                    ops            : wgpu::Operations {
+                        load  : match self.blend {
+                            BlendMode::Replace => wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            _                  => wgpu::LoadOp::Load,
+                        },
+                        store : true,
+                    },
+                })
+            ],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(pipeline);
+        for (index, group) in groups.iter().enumerate() {
+            render_pass.set_bind_group(index as u32, group, &[]);
+        }
+
+        render_pass.draw(0 .. 3, 0 .. 1);
+    }
+}
+
+/// Chain of effects for one screen. Render the screen into `attachment`, then `apply` runs the effects
+/// in order, ping-ponging between two window sized targets, and blends the result over the frame.
+/// The result is treated as premultiplied, clear `attachment` to transparent to keep what earlier screens drew.
+pub struct PostProcess {
+    pub effects : Vec<Box<dyn Effect>>,
+
+    targets   : [RenderTarget; 2],
+    composite : FullscreenPass,
+}
+
+impl PostProcess {
+    pub fn new(graphics: &GraphicsContext) -> Self {
+        let targets = [
+            RenderTarget::window_sized(graphics, 1.0, false, wgpu::FilterMode::Linear, Some("post_process_0")),
+            RenderTarget::window_sized(graphics, 1.0, false, wgpu::FilterMode::Linear, Some("post_process_1")).single_sampled(graphics),
+        ];
+
+        let composite = FullscreenPass::blended(graphics, fullscreen_shader!("copy.wgsl"), &[targets[0].texture.layout()], BlendMode::Premultiplied);
+
+        return Self { effects: vec![], targets, composite };
+    }
+
+    pub fn push(&mut self, effect: impl Effect) -> &mut Self {
+        self.effects.push(Box::new(effect));
+        return self;
+    }
+
+    /// First effect of the given type, to change its parameters.
+    pub fn get_mut<E: Effect>(&mut self) -> Option<&mut E> {
+        return self.effects.iter_mut().find_map(|effect| (effect.as_mut() as &mut dyn Any).downcast_mut::<E>());
+    }
+
+    /// Where the screen renders to before the effects are applied.
+    pub fn target(&self) -> &RenderTarget {
+        return &self.targets[0];
+    }
+
+    pub fn attachment(&self) -> ColorTarget<'_> {
+        return self.targets[0].attachment();
+    }
+
+    /// Call from `Screen::resize`.
+    pub fn resize(&mut self, graphics: &GraphicsContext) {
+        for target in &mut self.targets {
+            target.resize_to_window(graphics);
+        }
+
+        for effect in &mut self.effects {
+            effect.resize(graphics);
+        }
+    }
+
+    /// Runs the enabled effects and blends the result over `output`, usually `GraphicsContext::attachment` of the frame.
+    pub fn apply<'a>(&mut self, graphics: &GraphicsContext, output: impl Into<ColorTarget<'a>>) {
+        let Self { effects, targets, composite } = self;
+
+        utils::submit(&graphics.queue, &graphics.device, |encoder| {
+            // Effects only write into the targets, which they replace, the frame is blended over last
+            let mut current = 0;
+            for effect in effects.iter_mut().filter(|effect| effect.enabled()) {
+                effect.apply(graphics, encoder, &targets[current].texture, targets[1 - current].view().into());
+                current = 1 - current;
+            }
+
+            composite.draw(encoder, output, &[targets[current].texture.group()]);
+        });
+    }
+}
\ No newline at end of file
@@ -0,0 +1,109 @@
+use std::f32::consts::{PI, TAU};
+
+use cgmath::{vec2, vec3, InnerSpace, Vector2};
+
+use crate::graphics::primitive::{mesh::{data::vertex::Vertex, index::Indices, simple::Mesh}, geometry::{arc_segments, cross, perpendicular}};
+
+use super::Curve;
+
+/// How far the outline of a body sits behind its center line. With a depth test the part closest to the curve wins
+/// wherever the body overlaps itself, so the gradient stays seamless across joins and crossings.
+/// This needs opaque output, fade a translucent body by drawing it to a target first.
+pub const BODY_DEPTH: f32 = 1.0;
+
+impl Curve {
+    /// Thick body along the curve with rounded ends and joins, `radius` out from the center line.
+    /// `uv.x` runs from 0 on the center line to 1 on the outline, `uv.y` from 0 to 1 along the curve,
+    /// so a body shader can look up its gradient directly. The center line lies at `z`, see `BODY_DEPTH`.
+    /// Triangles have mixed winding, the pipeline must not cull.
+    pub fn body(&self, radius: f32, z: f32) -> (Vec<Vertex>, Indices) {
+        let mut body = Body { vertices: vec![], indices: vec![], length: self.length(), z };
+        let steps = |angle: f32| arc_segments(radius, angle, self.tolerance);
+
+        let points = &self.points;
+        if points.len() == 1 {
+            body.fan(points[0], 0.0, vec2(radius, 0.0), TAU, steps(TAU));
+            return body.finish();
+        }
+
+        let normal = |i: usize| perpendicular((points[i + 1] - points[i]).normalize()) * radius;
+        let last = points.len() - 1;
+
+        body.fan(points[0], 0.0, normal(0), PI, steps(PI));
+        for i in 0 .. last {
+            body.segment(points[i], points[i + 1], self.distances[i], self.distances[i + 1], normal(i));
+
+            // Only the outer side of a join leaves a gap, the inner side overlaps the next segment
+            if i > 0 {
+                let (incoming, outgoing) = (normal(i - 1), normal(i));
+                let turn = cross(incoming, outgoing).atan2(incoming.dot(outgoing));
+                let outer = if turn > 0.0 { -incoming } else { incoming };
+                body.fan(points[i], self.distances[i], outer, turn, steps(turn));
+            }
+        }
+
+        body.fan(points[last], self.distances[last], -normal(last - 1), PI, steps(PI));
+        return body.finish();
+    }
+
+    /// Uploads `body` as a mesh, rebuild it with `Mesh::bake` after trimming.
+    pub fn body_mesh(&self, device: &wgpu::Device, radius: f32, z: f32) -> Mesh<Vertex> {
+        let (vertices, indices) = self.body(radius, z);
+        return Mesh::indexed(device, vertices, indices);
+    }
+}
+
+struct Body {
+    vertices : Vec<Vertex>,
+    indices  : Vec<u32>,
+    length   : f32,
+    z        : f32,
+}
+
+impl Body {
+    /// `across` is 0 on the center line and 1 on the outline.
+    fn vertex(&mut self, position: Vector2<f32>, distance: f32, across: f32) -> u32 {
+        let along = if self.length > 0.0 { distance / self.length } else { 0.0 };
+        self.vertices.push(Vertex {
+            pos : vec3(position.x, position.y, self.z - across * BODY_DEPTH),
+            uv  : vec2(across, along),
+        });
+
+        return self.vertices.len() as u32 - 1;
+    }
+
+    /// Two quads sharing the center line, so the depth falls off towards both edges.
+    fn segment(&mut self, a: Vector2<f32>, b: Vector2<f32>, from: f32, to: f32, normal: Vector2<f32>) {
+        let a_left = self.vertex(a + normal, from, 1.0);
+        let a_center = self.vertex(a, from, 0.0);
+        let a_right = self.vertex(a - normal, from, 1.0);
+        let b_left = self.vertex(b + normal, to, 1.0);
+        let b_center = self.vertex(b, to, 0.0);
+        let b_right = self.vertex(b - normal, to, 1.0);
+
+        self.indices.extend([a_left, a_center, b_center, b_center, b_left, a_left]);
+        self.indices.extend([a_center, a_right, b_right, b_right, b_center, a_center]);
+    }
+
+    /// Pie slice around `center` starting at the offset `from` and turning by `sweep` radians.
+    fn fan(&mut self, center: Vector2<f32>, distance: f32, from: Vector2<f32>, sweep: f32, steps: u32) {
+        let hub = self.vertex(center, distance, 0.0);
+        let mut previous = self.vertex(center + from, distance, 1.0);
+
+        for step in 1 ..= steps {
+            let (sin, cos) = (sweep * step as f32 / steps as f32).sin_cos();
+            let offset = vec2(from.x * cos - from.y * sin, from.x * sin + from.y * cos);
+            let next = self.vertex(center + offset, distance, 1.0);
+
+            self.indices.extend([hub, previous, next]);
+            previous = next;
+        }
+    }
+
+    fn finish(self) -> (Vec<Vertex>, Indices) {
+        let mut indices = Indices::U16(vec![]);
+        indices.extend(self.indices, 0);
+
+        return (self.vertices, indices);
+    }
+}
\ No newline at end of file
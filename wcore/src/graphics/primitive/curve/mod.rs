@@ -0,0 +1,315 @@
+mod body;
+
+use std::f32::consts::TAU;
+
+use cgmath::{InnerSpace, Vector2};
+
+use super::geometry::{arc_segments, cross, EPSILON};
+
+pub use self::body::BODY_DEPTH;
+
+/// Subdivisions of a single Bezier segment before it is taken as flat regardless.
+const MAX_SUBDIVISIONS: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveKind {
+    /// Straight lines between the control points.
+    Linear,
+    /// Bezier segments of any degree, a control point repeated twice ends one segment and starts the next.
+    Bezier,
+    /// Uniform spline passing through every control point.
+    CatmullRom,
+    /// Circular arc through exactly three points. Collinear points are joined by straight lines, any other count falls back to `Bezier`.
+    Circle,
+}
+
+/// Path flattened into a polyline within a tolerance, parameterized by arc length.
+/// Progress runs from 0 at the first control point to 1 at the end of the path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve {
+    points    : Vec<Vector2<f32>>,
+    /// Arc length from the start up to each point.
+    distances : Vec<f32>,
+    tolerance : f32,
+}
+
+impl Curve {
+    /// `tolerance` is the largest distance allowed between the path and its polyline, in the control points' units.
+    pub fn new(kind: CurveKind, control: &[Vector2<f32>], tolerance: f32) -> Self {
+        assert!(!control.is_empty(), "a curve needs at least one control point");
+        let tolerance = tolerance.max(EPSILON);
+
+        let mut points = vec![control[0]];
+        match kind {
+            CurveKind::Linear     => points.extend_from_slice(&control[1 ..]),
+            CurveKind::Bezier     => flatten_segments(control, tolerance, &mut points),
+            CurveKind::CatmullRom => flatten_catmull_rom(control, tolerance, &mut points),
+            CurveKind::Circle     => match control {
+                &[a, b, c] => if !flatten_arc(a, b, c, tolerance, &mut points) {
+                    points.extend_from_slice(&control[1 ..]);
+                },
+                _ => flatten_segments(control, tolerance, &mut points),
+            },
+        }
+
+        return Self::from_points(points, tolerance);
+    }
+
+    fn from_points(mut points: Vec<Vector2<f32>>, tolerance: f32) -> Self {
+        points.dedup_by(|b, a| (*b - *a).magnitude() <= EPSILON);
+
+        let mut distances = Vec::with_capacity(points.len());
+        let mut distance = 0.0;
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 {
+                distance += (point - points[i - 1]).magnitude();
+            }
+
+            distances.push(distance);
+        }
+
+        return Self { points, distances, tolerance };
+    }
+
+    /// The flattened polyline.
+    pub fn points(&self) -> &[Vector2<f32>] {
+        return &self.points;
+    }
+
+    pub fn length(&self) -> f32 {
+        return self.distances[self.distances.len() - 1];
+    }
+
+    /// Point at a fraction of the arc length, clamped to the ends.
+    pub fn position_at(&self, progress: f32) -> Vector2<f32> {
+        let (i, t) = self.locate(progress);
+        return match self.points.get(i + 1) {
+            Some(&next) => self.points[i] + (next - self.points[i]) * t,
+            None        => self.points[i],
+        };
+    }
+
+    /// Unit tangent at a fraction of the arc length, zero if the curve is a single point.
+    pub fn direction_at(&self, progress: f32) -> Vector2<f32> {
+        if self.points.len() < 2 {
+            return Vector2::new(0.0, 0.0);
+        }
+
+        let (i, _) = self.locate(progress);
+        let i = i.min(self.points.len() - 2);
+        return (self.points[i + 1] - self.points[i]).normalize();
+    }
+
+    /// Part of the curve between two fractions of its arc length, so bodies can grow in and shrink away.
+    pub fn trim(&self, from: f32, to: f32) -> Self {
+        let (from, to) = (from.clamp(0.0, 1.0), to.clamp(0.0, 1.0));
+        if from >= to {
+            return Self::from_points(vec![self.position_at(from)], self.tolerance);
+        }
+
+        let (first, _) = self.locate(from);
+        let (last, _) = self.locate(to);
+
+        let mut points = vec![self.position_at(from)];
+        points.extend_from_slice(&self.points[first + 1 .. last + 1]);
+        points.push(self.position_at(to));
+
+        return Self::from_points(points, self.tolerance);
+    }
+
+    /// Index of the segment containing a fraction of the arc length, with the fraction along that segment.
+    fn locate(&self, progress: f32) -> (usize, f32) {
+        let distance = progress.clamp(0.0, 1.0) * self.length();
+        let i = self.distances.partition_point(|&d| d <= distance).saturating_sub(1);
+
+        return match self.distances.get(i + 1) {
+            Some(&next) => (i, ((distance - self.distances[i]) / (next - self.distances[i])).clamp(0.0, 1.0)),
+            None        => (i, 0.0),
+        };
+    }
+}
+
+/// Splits the control points at repeated points and flattens each Bezier segment, skipping the first point.
+fn flatten_segments(control: &[Vector2<f32>], tolerance: f32, out: &mut Vec<Vector2<f32>>) {
+    let mut start = 0;
+    for i in 1 ..= control.len() {
+        let split = i == control.len() || (control[i] - control[i - 1]).magnitude() <= EPSILON;
+        if split {
+            flatten_bezier(&control[start .. i], tolerance, MAX_SUBDIVISIONS, out);
+            start = i;
+        }
+    }
+}
+
+fn flatten_bezier(control: &[Vector2<f32>], tolerance: f32, depth: u32, out: &mut Vec<Vector2<f32>>) {
+    if control.len() < 3 {
+        out.extend_from_slice(&control[1 ..]);
+        return;
+    }
+
+    // The curve strays at most n(n - 1) / 8 times the largest second difference from its control polygon
+    let degree = (control.len() - 1) as f32;
+    let bend = control.windows(3).map(|w| (w[0] - w[1] * 2.0 + w[2]).magnitude()).fold(0.0, f32::max);
+    if depth == 0 || degree * (degree - 1.0) / 8.0 * bend <= tolerance {
+        out.push(control[control.len() - 1]);
+        return;
+    }
+
+    // De Casteljau at the midpoint, the left edge of the triangle is one half and the right edge the other
+    let mut left = Vec::with_capacity(control.len());
+    let mut right = Vec::with_capacity(control.len());
+    let mut row = control.to_vec();
+    while !row.is_empty() {
+        left.push(row[0]);
+        right.push(row[row.len() - 1]);
+        row = row.windows(2).map(|w| (w[0] + w[1]) * 0.5).collect();
+    }
+
+    right.reverse();
+    flatten_bezier(&left, tolerance, depth - 1, out);
+    flatten_bezier(&right, tolerance, depth - 1, out);
+}
+
+/// Each span is turned into the equivalent cubic Bezier, the ends repeat their point for a missing neighbour.
+fn flatten_catmull_rom(control: &[Vector2<f32>], tolerance: f32, out: &mut Vec<Vector2<f32>>) {
+    for i in 0 .. control.len().saturating_sub(1) {
+        let p0 = control[i.saturating_sub(1)];
+        let (p1, p2) = (control[i], control[i + 1]);
+        let p3 = control[(i + 2).min(control.len() - 1)];
+
+        let cubic = [p1, p1 + (p2 - p0) / 6.0, p2 - (p3 - p1) / 6.0, p2];
+        flatten_bezier(&cubic, tolerance, MAX_SUBDIVISIONS, out);
+    }
+}
+
+/// Arc from `a` through `b` to `c`, skipping `a`. Returns false without adding anything if the points are collinear.
+fn flatten_arc(a: Vector2<f32>, b: Vector2<f32>, c: Vector2<f32>, tolerance: f32, out: &mut Vec<Vector2<f32>>) -> bool {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2.0 * cross(ab, ac);
+    if d.abs() <= EPSILON * ab.magnitude() * ac.magnitude() {
+        return false;
+    }
+
+    // Circumcenter relative to `a`
+    let center = a + Vector2::new(ac.y * ab.magnitude2() - ab.y * ac.magnitude2(), ab.x * ac.magnitude2() - ac.x * ab.magnitude2()) / d;
+    let radius = (a - center).magnitude();
+
+    let (start, end) = (angle(a - center), angle(c - center));
+    let sweep = match d > 0.0 {
+        true  => (end - start).rem_euclid(TAU),
+        false => -(start - end).rem_euclid(TAU),
+    };
+
+    let steps = arc_segments(radius, sweep, tolerance);
+    for step in 1 .. steps {
+        let (sin, cos) = (start + sweep * step as f32 / steps as f32).sin_cos();
+        out.push(center + Vector2::new(cos, sin) * radius);
+    }
+
+    out.push(c);
+    return true;
+}
+
+fn angle(v: Vector2<f32>) -> f32 {
+    return v.y.atan2(v.x);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use cgmath::vec2;
+
+    use super::*;
+
+    fn assert_close(a: f32, b: f32, epsilon: f32) {
+        assert!((a - b).abs() <= epsilon, "{a} != {b}");
+    }
+
+    fn line() -> Curve {
+        return Curve::new(CurveKind::Linear, &[vec2(0.0, 0.0), vec2(6.0, 8.0)], 0.01);
+    }
+
+    #[test]
+    fn measures_lines_and_arcs() {
+        assert_eq!(line().length(), 10.0);
+        assert_eq!(line().position_at(0.5), vec2(3.0, 4.0));
+        assert_eq!(line().direction_at(0.5), vec2(0.6, 0.8));
+
+        // Half a circle of radius 10 bending up through the top
+        let arc = Curve::new(CurveKind::Circle, &[vec2(-10.0, 0.0), vec2(0.0, 10.0), vec2(10.0, 0.0)], 0.01);
+        assert_close(arc.length(), PI * 10.0, 0.01);
+        assert!(arc.points().iter().all(|point| (point.magnitude() - 10.0).abs() < 1e-3));
+        assert_close(arc.position_at(0.5).y, 10.0, 0.01);
+    }
+
+    #[test]
+    fn passes_through_the_ends() {
+        let control = [vec2(0.0, 0.0), vec2(5.0, 10.0), vec2(10.0, 0.0), vec2(15.0, 5.0)];
+        for kind in [CurveKind::Linear, CurveKind::Bezier, CurveKind::CatmullRom] {
+            let curve = Curve::new(kind, &control, 0.1);
+            assert_eq!(curve.points()[0], control[0]);
+            assert_eq!(curve.points()[curve.points().len() - 1], control[3]);
+        }
+
+        // Catmull-Rom goes through every control point, not just the ends
+        let curve = Curve::new(CurveKind::CatmullRom, &control, 0.1);
+        assert!(control.iter().all(|point| curve.points().contains(point)));
+    }
+
+    #[test]
+    fn collinear_circle_is_linear() {
+        for control in [[vec2(0.0, 0.0), vec2(5.0, 0.0), vec2(10.0, 0.0)], [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(5.0, 0.0)]] {
+            assert_eq!(Curve::new(CurveKind::Circle, &control, 0.1), Curve::new(CurveKind::Linear, &control, 0.1));
+        }
+    }
+
+    #[test]
+    fn single_point_curve() {
+        let curve = Curve::new(CurveKind::Bezier, &[vec2(1.0, 2.0)], 0.1);
+        assert_eq!(curve.length(), 0.0);
+        assert_eq!(curve.position_at(0.7), vec2(1.0, 2.0));
+        assert_eq!(curve.direction_at(0.7), vec2(0.0, 0.0));
+        assert_eq!(curve.trim(0.2, 0.8), curve);
+
+        // Repeated control points collapse into one
+        assert_eq!(Curve::new(CurveKind::Linear, &[vec2(1.0, 2.0), vec2(1.0, 2.0)], 0.1), curve);
+    }
+
+    #[test]
+    fn trims_by_arc_length() {
+        let curve = Curve::new(CurveKind::Linear, &[vec2(0.0, 0.0), vec2(4.0, 0.0), vec2(4.0, 6.0)], 0.01);
+        assert_eq!(curve.trim(0.0, 1.0), curve);
+        assert_eq!(curve.trim(-1.0, 2.0), curve);
+
+        let middle = curve.trim(0.2, 0.6);
+        assert_close(middle.length(), 4.0, 1e-5);
+        assert_eq!(middle.points(), [vec2(2.0, 0.0), vec2(4.0, 0.0), vec2(4.0, 2.0)]);
+
+        // Nothing left in between, but the point is kept so the body still has a dot to draw
+        for (from, to) in [(0.5, 0.5), (0.8, 0.3)] {
+            let trimmed = curve.trim(from, to);
+            assert_eq!(trimmed.points(), [curve.position_at(from)]);
+            assert_eq!(trimmed.length(), 0.0);
+        }
+    }
+
+    #[test]
+    fn builds_bodies() {
+        // With the tolerance above the radius every half turn takes two steps: a fan of three triangles
+        // around a hub for each cap, and two quads for the segment between them
+        let line_body = Curve { tolerance: 1.0, .. line() }.body(0.5, 0.0);
+        assert_eq!(line_body.0.len(), 4 + 4 + 6);
+        assert_eq!(line_body.1.len(), 2 * 3 + 2 * 3 + 12);
+
+        let along = line_body.0.iter().map(|vertex| vertex.uv.y);
+        assert_eq!(along.clone().fold(f32::MAX, f32::min), 0.0);
+        assert_eq!(along.fold(f32::MIN, f32::max), 1.0);
+        assert!(line_body.0.iter().all(|vertex| vertex.pos.z == -vertex.uv.x * BODY_DEPTH));
+
+        // A single point is a full circle of four steps
+        let (vertices, indices) = Curve::new(CurveKind::Linear, &[vec2(0.0, 0.0)], 1.0).body(0.5, 0.0);
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(indices.len(), 12);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,25 @@
+use std::f32::consts::FRAC_PI_2;
+
+use cgmath::{vec2, Vector2};
+
+/// Points closer than this are merged, there is no direction to offset them along.
+pub(crate) const EPSILON: f32 = 1e-4;
+
+/// Number of segments for an arc that stays within `tolerance` of the true circle.
+pub(crate) fn arc_segments(radius: f32, angle: f32, tolerance: f32) -> u32 {
+    let step = match radius > tolerance {
+        true  => 2.0 * (1.0 - tolerance / radius).acos(),
+        false => FRAC_PI_2,
+    };
+
+    return ((angle.abs() / step).ceil() as u32).clamp(1, 1024);
+}
+
+/// Left of `direction` in y-up coordinates, right of it on screen.
+pub(crate) fn perpendicular(direction: Vector2<f32>) -> Vector2<f32> {
+    return vec2(-direction.y, direction.x);
+}
+
+pub(crate) fn cross(a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    return a.x * b.y - a.y * b.x;
+}
\ No newline at end of file
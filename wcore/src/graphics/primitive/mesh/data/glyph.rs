@@ -0,0 +1,32 @@
+use crate::graphics::primitive::mesh::{instanced::Instance, layout::VertexLayout};
+
+#[repr(C)]
+#[derive(Copy, Clone, VertexLayout)]
+#[step(instance)]
+#[location(5)]
+pub struct GlyphRaw {
+    rect  : [f32; 4],
+    uv    : [f32; 4],
+    color : [f32; 4],
+}
+
+/// A single quad cut out of the glyph atlas.
+pub struct Glyph {
+    /// x, y, width, height in scene units, y pointing down.
+    pub rect  : cgmath::Vector4<f32>,
+    /// Top left and bottom right corner in the atlas.
+    pub uv    : cgmath::Vector4<f32>,
+    pub color : cgmath::Vector4<f32>,
+}
+
+impl Instance for Glyph {
+    fn to_raw(&self) -> GlyphRaw {
+        return GlyphRaw {
+            rect  : self.rect.into(),
+            uv    : self.uv.into(),
+            color : self.color.into(),
+        };
+    }
+
+    type InstanceRaw = GlyphRaw;
+}
@@ -1,2 +1,5 @@
 pub mod vertex;
-pub mod model;
\ No newline at end of file
+pub mod model;
+pub mod glyph;
+pub mod sprite;
+pub mod shape;
\ No newline at end of file
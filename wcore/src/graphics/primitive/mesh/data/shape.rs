@@ -0,0 +1,11 @@
+use cgmath::{Vector2, Vector4};
+
+use crate::graphics::primitive::mesh::layout::VertexLayout;
+
+/// Vertex of tessellated shapes, the color already includes the anti-aliasing coverage in its alpha.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, VertexLayout)]
+pub struct ShapeVertex {
+    pub pos   : Vector2<f32>,
+    pub color : Vector4<f32>,
+}
\ No newline at end of file
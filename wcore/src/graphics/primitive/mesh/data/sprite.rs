@@ -0,0 +1,39 @@
+use cgmath::{Vector2, Vector4};
+
+use crate::graphics::primitive::{mesh::{instanced::Instance, layout::VertexLayout}, rect::Rect};
+
+#[repr(C)]
+#[derive(Copy, Clone, VertexLayout)]
+#[step(instance)]
+#[location(5)]
+pub struct SpriteRaw {
+    rect      : [f32; 4],
+    uv        : [f32; 4],
+    color     : [f32; 4],
+    /// Origin x, origin y, rotation, unused.
+    transform : [f32; 4],
+}
+
+pub struct Sprite {
+    /// Destination before rotation, y pointing down.
+    pub rect     : Rect,
+    pub uv       : Rect,
+    pub color    : Vector4<f32>,
+    /// Clockwise in radians, around `origin`.
+    pub rotation : f32,
+    /// Pivot relative to the top left corner of `rect`.
+    pub origin   : Vector2<f32>,
+}
+
+impl Instance for Sprite {
+    fn to_raw(&self) -> SpriteRaw {
+        return SpriteRaw {
+            rect      : Vector4::from(self.rect).into(),
+            uv        : [self.uv.x, self.uv.y, self.uv.right(), self.uv.bottom()],
+            color     : self.color.into(),
+            transform : [self.origin.x, self.origin.y, self.rotation, 0.0],
+        };
+    }
+
+    type InstanceRaw = SpriteRaw;
+}
\ No newline at end of file
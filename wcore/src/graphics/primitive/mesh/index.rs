@@ -0,0 +1,113 @@
+use wgpu::util::DeviceExt;
+
+/// Index data of a mesh, `U16` halves the size for meshes with up to 65536 vertices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    pub fn len(&self) -> usize {
+        return match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    pub fn format(&self) -> wgpu::IndexFormat {
+        return match self {
+            Indices::U16(_) => wgpu::IndexFormat::Uint16,
+            Indices::U32(_) => wgpu::IndexFormat::Uint32,
+        };
+    }
+
+    /// Offsets every index by `base`, for appending geometry to a mesh. Switches to `U32` if needed.
+    pub fn extend(&mut self, indices: impl IntoIterator<Item = u32>, base: u32) {
+        let indices = indices.into_iter().map(|index| index + base).collect::<Vec<_>>();
+        let max = indices.iter().copied().max().unwrap_or(0);
+
+        if let Indices::U16(current) = self {
+            if max > u16::MAX as u32 {
+                *self = Indices::U32(current.iter().map(|&index| index as u32).collect());
+            }
+        }
+
+        match self {
+            Indices::U16(current) => current.extend(indices.iter().map(|&index| index as u16)),
+            Indices::U32(current) => current.extend(indices),
+        }
+    }
+
+    /// Raw bytes padded to `wgpu::COPY_BUFFER_ALIGNMENT`, as buffer writes require.
+    fn bytes(&self) -> Vec<u8> {
+        let mut bytes = match self {
+            Indices::U16(indices) => bytemuck::cast_slice(indices).to_vec(),
+            Indices::U32(indices) => bytemuck::cast_slice(indices).to_vec(),
+        };
+
+        let alignment = wgpu::COPY_BUFFER_ALIGNMENT as usize;
+        bytes.resize(bytes.len().div_ceil(alignment).max(1) * alignment, 0);
+
+        return bytes;
+    }
+
+    pub(crate) fn make_buffer(&self, device: &wgpu::Device) -> wgpu::Buffer {
+        return device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label    : Some("Index Buffer"),
+                contents : &self.bytes(),
+                usage    : wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+    }
+
+    /// The buffer must be large enough to hold all indices.
+    pub(crate) fn write(&self, queue: &wgpu::Queue, buffer: &wgpu::Buffer) {
+        queue.write_buffer(buffer, 0, &self.bytes());
+    }
+}
+
+/// Index buffer that remembers what it was made for, so updates know when it has to be recreated.
+pub(crate) struct IndexBuffer {
+    pub buffer : wgpu::Buffer,
+    size       : usize,
+    format     : wgpu::IndexFormat,
+}
+
+impl IndexBuffer {
+    pub fn new(device: &wgpu::Device, indices: &Indices) -> Self {
+        return Self {
+            buffer : indices.make_buffer(device),
+            size   : indices.bytes().len(),
+            format : indices.format(),
+        };
+    }
+
+    /// Reuses the buffer unless the indices outgrew it or changed format.
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, indices: &Indices) {
+        let bytes = indices.bytes();
+        if bytes.len() > self.size || indices.format() != self.format {
+            *self = Self::new(device, indices);
+            return;
+        }
+
+        queue.write_buffer(&self.buffer, 0, &bytes);
+    }
+}
+
+impl From<Vec<u16>> for Indices {
+    fn from(indices: Vec<u16>) -> Self {
+        return Indices::U16(indices);
+    }
+}
+
+impl From<Vec<u32>> for Indices {
+    fn from(indices: Vec<u32>) -> Self {
+        return Indices::U32(indices);
+    }
+}
\ No newline at end of file
@@ -1,11 +1,9 @@
-use std::marker::PhantomData;
-
 use bytemuck::{Zeroable, Pod};
 use wgpu::util::DeviceExt;
 
 use crate::graphics::drawable::Drawable;
 
-use super::simple::Mesh;
+use super::{simple::Mesh, index::{Indices, IndexBuffer}};
 
 /* Instanced Mesh */
 pub trait Instance {
@@ -14,11 +12,15 @@ pub trait Instance {
 }
 
 pub struct InstancedMesh<I: Instance, V: Pod + Zeroable> {
-        buffer          : wgpu::Buffer,
-        instance_buffer : wgpu::Buffer,
+        buffer            : wgpu::Buffer,
+        index_buffer      : Option<IndexBuffer>,
+        instance_buffer   : wgpu::Buffer,
+        instance_capacity : usize,
 
-    pub vertices        : Vec<V>,
-    pub instances       : Vec<I>,
+    pub vertices          : Vec<V>,
+    /// Drawn with `draw_indexed` if set, changes need a `bake` like the vertices.
+    pub indices           : Option<Indices>,
+    pub instances         : Vec<I>,
 }
 
 impl<I: Instance, V: Pod + Zeroable> InstancedMesh<I, V> {
@@ -29,26 +31,66 @@ impl<I: Instance, V: Pod + Zeroable> InstancedMesh<I, V> {
 
         return Self {
             buffer,
+            index_buffer: None,
             instance_buffer,
+            instance_capacity: instance_data.len(),
 
             vertices,
+            indices: None,
             instances,
         };
     }
 
+    pub fn indexed(device: &wgpu::Device, vertices: Vec<V>, indices: impl Into<Indices>, instances: Vec<I>) -> Self {
+        let indices = indices.into();
+        let mut mesh = Self::new(device, vertices, instances);
+        mesh.index_buffer = Some(IndexBuffer::new(device, &indices));
+        mesh.indices = Some(indices);
+
+        return mesh;
+    }
+
     pub fn bake(&mut self, device: &wgpu::Device) {
         self.buffer = Mesh::make_buffer(device, &self.vertices);
+        self.index_buffer = self.indices.as_ref().map(|indices| IndexBuffer::new(device, indices));
     }
 
     pub fn bake_instances(&mut self, device: &wgpu::Device) {
         let instance_data = self.instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
         self.instance_buffer = InstancedMesh::<I, V>::make_buffer(device, &instance_data);
+        self.instance_capacity = instance_data.len();
+    }
+
+    /// Like `bake_instances`, but reuses the instance buffer unless it has to grow.
+    pub fn upload_instances(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let instance_data = self.instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
+        if instance_data.len() > self.instance_capacity {
+            self.instance_capacity = instance_data.len().next_power_of_two();
+            self.instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label              : None,
+                size               : (self.instance_capacity * std::mem::size_of::<I::InstanceRaw>()) as wgpu::BufferAddress,
+                usage              : wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation : false,
+            });
+        }
+
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instance_data));
     }
 
     pub fn update(&mut self, data: &[V], queue: &wgpu::Queue) {
         queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&data));
     }
 
+    /// Replaces the baked indices, the buffer is only recreated if they outgrow it or change format.
+    pub fn update_indices(&mut self, indices: Indices, device: &wgpu::Device, queue: &wgpu::Queue) {
+        match &mut self.index_buffer {
+            Some(buffer) => buffer.write(device, queue, &indices),
+            None         => self.index_buffer = Some(IndexBuffer::new(device, &indices)),
+        }
+
+        self.indices = Some(indices);
+    }
+
     pub fn update_instances(&mut self, data: &[I::InstanceRaw], queue: &wgpu::Queue) {
         queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(data));
     }
@@ -66,10 +108,19 @@ impl<I: Instance, V: Pod + Zeroable> InstancedMesh<I, V> {
 
 impl<I: Instance, V: Pod + Zeroable> Drawable for InstancedMesh<I, V> {
     fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
-        if self.instances.len() > 0 {
+        if !self.instances.is_empty() {
             render_pass.set_vertex_buffer(0, self.buffer.slice(..));
             render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
-            render_pass.draw(0 .. self.vertices.len() as u32, 0 .. self.instances.len() as u32);
+
+            let instances = 0 .. self.instances.len() as u32;
+            match (&self.indices, &self.index_buffer) {
+                (Some(indices), Some(buffer)) => {
+                    render_pass.set_index_buffer(buffer.buffer.slice(..), indices.format());
+                    render_pass.draw_indexed(0 .. indices.len() as u32, 0, instances);
+                }
+
+                _ => render_pass.draw(0 .. self.vertices.len() as u32, instances),
+            }
         }
     }
 }
@@ -0,0 +1,63 @@
+use bytemuck::Pod;
+use cgmath::{Vector2, Vector3, Vector4, Point2, Point3, Matrix2, Matrix3, Matrix4};
+
+pub use wcore_derive::VertexLayout;
+
+/// Vertex or instance buffer layout, usually derived with `#[derive(VertexLayout)]`.
+pub trait VertexLayout: Pod {
+    const STEP_MODE  : wgpu::VertexStepMode;
+    const ATTRIBUTES : &'static [wgpu::VertexAttribute];
+
+    fn describe<'a>() -> wgpu::VertexBufferLayout<'a> {
+        return wgpu::VertexBufferLayout {
+            array_stride : std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode    : Self::STEP_MODE,
+            attributes   : Self::ATTRIBUTES,
+        };
+    }
+}
+
+/// A field type of a `VertexLayout` struct, with the attribute formats it is made of.
+///
+/// # Safety
+/// The type must be valid for any bit pattern and have no padding, laid out exactly as `FORMATS` back to back.
+pub unsafe trait VertexField: Copy + 'static {
+    const FORMATS: &'static [wgpu::VertexFormat];
+}
+
+macro_rules! vertex_field {
+    ($($ty: ty => [$($format: ident),+]),+ $(,)?) => {
+        $(unsafe impl VertexField for $ty {
+            const FORMATS: &'static [wgpu::VertexFormat] = &[$(wgpu::VertexFormat::$format),+];
+        })+
+    };
+}
+
+vertex_field! {
+    f32          => [Float32],
+    [f32; 2]     => [Float32x2],
+    [f32; 3]     => [Float32x3],
+    [f32; 4]     => [Float32x4],
+    u32          => [Uint32],
+    [u32; 2]     => [Uint32x2],
+    [u32; 3]     => [Uint32x3],
+    [u32; 4]     => [Uint32x4],
+    i32          => [Sint32],
+    [i32; 2]     => [Sint32x2],
+    [i32; 3]     => [Sint32x3],
+    [i32; 4]     => [Sint32x4],
+
+    Vector2<f32> => [Float32x2],
+    Vector3<f32> => [Float32x3],
+    Vector4<f32> => [Float32x4],
+    Point2<f32>  => [Float32x2],
+    Point3<f32>  => [Float32x3],
+
+    // Matrices take one attribute per column
+    [[f32; 2]; 2] => [Float32x2, Float32x2],
+    [[f32; 3]; 3] => [Float32x3, Float32x3, Float32x3],
+    [[f32; 4]; 4] => [Float32x4, Float32x4, Float32x4, Float32x4],
+    Matrix2<f32>  => [Float32x2, Float32x2],
+    Matrix3<f32>  => [Float32x3, Float32x3, Float32x3],
+    Matrix4<f32>  => [Float32x4, Float32x4, Float32x4, Float32x4],
+}
\ No newline at end of file
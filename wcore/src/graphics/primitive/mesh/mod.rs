@@ -1,3 +1,5 @@
 pub mod data;
 pub mod simple;
-pub mod instanced;
\ No newline at end of file
+pub mod instanced;
+pub mod index;
+pub mod layout;
\ No newline at end of file
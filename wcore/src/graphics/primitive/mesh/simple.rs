@@ -3,10 +3,15 @@ use wgpu::util::DeviceExt;
 
 use crate::graphics::drawable::Drawable;
 
+use super::index::{Indices, IndexBuffer};
+
 /* Mesh */
 pub struct Mesh<V: Pod + Zeroable> {
-        buffer   : wgpu::Buffer,
-    pub vertices : Vec<V>
+        buffer       : wgpu::Buffer,
+        index_buffer : Option<IndexBuffer>,
+    pub vertices     : Vec<V>,
+    /// Drawn with `draw_indexed` if set, changes need a `bake` like the vertices.
+    pub indices      : Option<Indices>,
 }
 
 impl<V: Pod + Zeroable> Mesh<V> {
@@ -15,19 +20,44 @@ impl<V: Pod + Zeroable> Mesh<V> {
 
         return Self {
             buffer,
+            index_buffer: None,
             vertices,
+            indices: None,
+        };
+    }
+
+    pub fn indexed(device: &wgpu::Device, vertices: Vec<V>, indices: impl Into<Indices>) -> Self {
+        let indices = indices.into();
+        let buffer = Mesh::make_buffer(device, &vertices);
+
+        return Self {
+            buffer,
+            index_buffer: Some(IndexBuffer::new(device, &indices)),
+            vertices,
+            indices: Some(indices),
         };
     }
 
     // TODO: opt for a safer approach and make vertices private?
     pub fn bake(&mut self, device: &wgpu::Device) {
         self.buffer = Mesh::make_buffer(device, &self.vertices);
+        self.index_buffer = self.indices.as_ref().map(|indices| IndexBuffer::new(device, indices));
     }
 
     pub fn update(&mut self, data: &[V], queue: &wgpu::Queue) {
         queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&data));
     }
 
+    /// Replaces the baked indices, the buffer is only recreated if they outgrow it or change format.
+    pub fn update_indices(&mut self, indices: Indices, device: &wgpu::Device, queue: &wgpu::Queue) {
+        match &mut self.index_buffer {
+            Some(buffer) => buffer.write(device, queue, &indices),
+            None         => self.index_buffer = Some(IndexBuffer::new(device, &indices)),
+        }
+
+        self.indices = Some(indices);
+    }
+
     pub(crate) fn make_buffer(device: &wgpu::Device, vertices: &[V]) -> wgpu::Buffer {
         return device.create_buffer_init(
             &wgpu::util::BufferInitDescriptor {
@@ -43,6 +73,14 @@ impl<V: Pod + Zeroable> Drawable for Mesh<V> {
     fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
         let slice = self.buffer.slice(..);
         render_pass.set_vertex_buffer(0, slice);
-        render_pass.draw(0 .. self.vertices.len() as u32, 0 .. 1);
+
+        match (&self.indices, &self.index_buffer) {
+            (Some(indices), Some(buffer)) => {
+                render_pass.set_index_buffer(buffer.buffer.slice(..), indices.format());
+                render_pass.draw_indexed(0 .. indices.len() as u32, 0, 0 .. 1);
+            }
+
+            _ => render_pass.draw(0 .. self.vertices.len() as u32, 0 .. 1),
+        }
     }
 }
\ No newline at end of file
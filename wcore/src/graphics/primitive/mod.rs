@@ -1 +1,4 @@
-pub mod mesh;
\ No newline at end of file
+pub mod mesh;
+pub mod rect;
+pub mod curve;
+pub(crate) mod geometry;
\ No newline at end of file
@@ -0,0 +1,32 @@
+use cgmath::{Vector4, vec4};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x      : f32,
+    pub y      : f32,
+    pub width  : f32,
+    pub height : f32,
+}
+
+impl Rect {
+    /// The whole texture in UV space.
+    pub const UNIT: Rect = Rect { x: 0.0, y: 0.0, width: 1.0, height: 1.0 };
+
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        return Self { x, y, width, height };
+    }
+
+    pub fn right(&self) -> f32 {
+        return self.x + self.width;
+    }
+
+    pub fn bottom(&self) -> f32 {
+        return self.y + self.height;
+    }
+}
+
+impl From<Rect> for Vector4<f32> {
+    fn from(rect: Rect) -> Self {
+        return vec4(rect.x, rect.y, rect.width, rect.height);
+    }
+}
\ No newline at end of file
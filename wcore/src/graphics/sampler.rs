@@ -0,0 +1,117 @@
+use std::{cell::RefCell, collections::HashMap, num::NonZeroU8, sync::Arc};
+
+use wgpu::{AddressMode, FilterMode};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SamplerSettings {
+    pub address_mode_u : AddressMode,
+    pub address_mode_v : AddressMode,
+    pub mag_filter     : FilterMode,
+    pub min_filter     : FilterMode,
+    pub mipmap_filter  : FilterMode,
+    /// 1 disables anisotropic filtering, otherwise 2, 4, 8 or 16. Only used if all filters are linear.
+    pub anisotropy     : u8,
+    /// Added to the mip level picked by the hardware, negative values sharpen.
+    /// Applied in the shader, so it is stored per texture instead of in the shared sampler.
+    /// The built-in sprite, text and model shaders read it from binding 2 of the texture group, custom ones have to as well.
+    pub lod_bias       : f32,
+    pub lod_min_clamp  : f32,
+    pub lod_max_clamp  : f32,
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        return Self {
+            address_mode_u : AddressMode::ClampToEdge,
+            address_mode_v : AddressMode::ClampToEdge,
+            mag_filter     : FilterMode::Linear,
+            min_filter     : FilterMode::Linear,
+            mipmap_filter  : FilterMode::Linear,
+            anisotropy     : 1,
+            lod_bias       : 0.0,
+            lod_min_clamp  : 0.0,
+            lod_max_clamp  : f32::MAX,
+        };
+    }
+}
+
+impl From<FilterMode> for SamplerSettings {
+    fn from(filter_mode: FilterMode) -> Self {
+        return Self {
+            mag_filter    : filter_mode,
+            min_filter    : filter_mode,
+            mipmap_filter : FilterMode::Nearest,
+            .. Default::default()
+        };
+    }
+}
+
+impl SamplerSettings {
+    /// Repeats the texture in both directions, for tiling backgrounds.
+    pub fn repeat(self) -> Self {
+        return Self { address_mode_u: AddressMode::Repeat, address_mode_v: AddressMode::Repeat, .. self };
+    }
+
+    fn anisotropy_clamp(&self) -> Option<NonZeroU8> {
+        let linear = [self.mag_filter, self.min_filter, self.mipmap_filter].iter().all(|filter| *filter == FilterMode::Linear);
+        if !linear || self.anisotropy <= 1 {
+            return None;
+        }
+
+        // Largest supported power of two not above the requested value
+        let anisotropy = 1 << (7 - self.anisotropy.min(16).leading_zeros());
+        return NonZeroU8::new(anisotropy);
+    }
+
+    fn key(&self) -> SamplerKey {
+        return (
+            self.address_mode_u,
+            self.address_mode_v,
+            self.mag_filter,
+            self.min_filter,
+            self.mipmap_filter,
+            self.anisotropy_clamp(),
+            self.lod_min_clamp.to_bits(),
+            self.lod_max_clamp.to_bits(),
+        );
+    }
+}
+
+type SamplerKey = (AddressMode, AddressMode, FilterMode, FilterMode, FilterMode, Option<NonZeroU8>, u32, u32);
+
+/// Samplers by settings, textures with the same settings share one sampler.
+#[derive(Default)]
+pub struct SamplerCache {
+    samplers: RefCell<HashMap<SamplerKey, Arc<wgpu::Sampler>>>,
+}
+
+impl SamplerCache {
+    pub fn get(&self, device: &wgpu::Device, settings: &SamplerSettings) -> Arc<wgpu::Sampler> {
+        let mut samplers = self.samplers.borrow_mut();
+        let sampler = samplers.entry(settings.key()).or_insert_with(|| Arc::new(device.create_sampler(
+            &wgpu::SamplerDescriptor {
+                label            : Some("Shared Sampler"),
+                address_mode_u   : settings.address_mode_u,
+                address_mode_v   : settings.address_mode_v,
+                address_mode_w   : AddressMode::ClampToEdge,
+                mag_filter       : settings.mag_filter,
+                min_filter       : settings.min_filter,
+                mipmap_filter    : settings.mipmap_filter,
+                lod_min_clamp    : settings.lod_min_clamp,
+                lod_max_clamp    : settings.lod_max_clamp,
+                anisotropy_clamp : settings.anisotropy_clamp(),
+                .. Default::default()
+            }
+        )));
+
+        return sampler.clone();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.samplers.borrow().len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.samplers.borrow().is_empty();
+    }
+}
\ No newline at end of file
@@ -2,7 +2,7 @@ use cgmath::Matrix4;
 
 use super::{camera::{ProjectionOrthographic, Camera2D, Transformation}, uniform::Uniform, bindable::Bindable};
 
-pub trait Scene: Bindable<[[f32; 4]; 4]> + Transformation {}
+pub trait Scene: Bindable + Transformation {}
 
 impl Scene for Scene2D {}
 pub struct Scene2D {
@@ -12,20 +12,20 @@ pub struct Scene2D {
 }
 
 impl Scene2D {
-    pub fn new(graphics: &wgpu::Device, width: u32, height: u32) -> Self {
+    pub fn new(graphics: &wgpu::Device, width: u32, height: u32, scale_factor: f64) -> Self {
         return Self {
-            projection : ProjectionOrthographic::new(width, height, -100.0, 100.0),
+            projection : ProjectionOrthographic::new(width, height, scale_factor, -100.0, 100.0),
             camera     : Camera2D { position: (0.0, 0.0, -50.0).into() },
             uniform    : Uniform::new(graphics),
         };
     }
 
     pub fn update(&self, queue: &wgpu::Queue) {
-        Bindable::update(self, queue, &(self.apply()).into());
+        self.uniform.update(queue, &(self.apply()).into());
     }
 }
 
-impl Bindable<[[f32; 4]; 4]> for Scene2D {
+impl Bindable for Scene2D {
     fn bind<'a, 'b: 'a>(&'b self, render_pass: &mut wgpu::RenderPass<'a>, index: u32) {
         self.uniform.bind(render_pass, index);
     }
@@ -34,14 +34,13 @@ impl Bindable<[[f32; 4]; 4]> for Scene2D {
         return self.uniform.layout();
     }
 
-    fn group(&self) -> &wgpu::BindGroup {
-        return self.uniform.group();
+    fn layout_entries(&self) -> &'static [wgpu::BindGroupLayoutEntry] {
+        return self.uniform.layout_entries();
     }
 
-    fn update(&self, queue: &wgpu::Queue, value: &[[f32; 4]; 4]) {
-        self.uniform.update(queue, value);
+    fn group(&self) -> &wgpu::BindGroup {
+        return self.uniform.group();
     }
-
 }
 
 impl Transformation for Scene2D {
@@ -1,6 +1,47 @@
+use instant::Duration;
+
+use crate::input::Input;
+
 use super::context::GraphicsContext;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedrawPolicy {
+	/// Redraw as fast as the present mode and frame cap allow.
+	Continuous,
+	/// Redraw only on input or after [`AppHandle::request_redraw`](crate::handle::AppHandle::request_redraw).
+	OnDemand,
+	/// Redraw continuously, but at most this many times per second.
+	Capped(u32),
+}
+
+impl RedrawPolicy {
+	/// Picks the policy that redraws more often.
+	pub fn max(self, other: Self) -> Self {
+		return match (self, other) {
+			(RedrawPolicy::Continuous, _) | (_, RedrawPolicy::Continuous) => RedrawPolicy::Continuous,
+			(RedrawPolicy::Capped(a), RedrawPolicy::Capped(b))            => RedrawPolicy::Capped(a.max(b)),
+			(RedrawPolicy::Capped(a), _) | (_, RedrawPolicy::Capped(a))   => RedrawPolicy::Capped(a),
+			(RedrawPolicy::OnDemand, RedrawPolicy::OnDemand)              => RedrawPolicy::OnDemand,
+		};
+	}
+}
+
+#[allow(unused_variables)]
 pub trait Screen<State> {
+	/// The app redraws according to the most demanding policy among its screens.
+	fn redraw_policy(&self) -> RedrawPolicy { RedrawPolicy::Continuous }
+
+	fn update(&mut self, state: &mut State, delta: Duration, graphics: &mut GraphicsContext) {}
 	fn render(&mut self, state: &mut State, view: &wgpu::TextureView, graphics: &mut GraphicsContext) {}
 	fn resize(&mut self, state: &mut State, width: u32, height: u32, graphics: &mut GraphicsContext) {}
-}
\ No newline at end of file
+	fn rescale(&mut self, state: &mut State, scale_factor: f64, graphics: &mut GraphicsContext) {}
+	/// Called after the graphics context was recreated, all GPU resources have to be rebuilt.
+	fn recreate(&mut self, state: &mut State, graphics: &mut GraphicsContext) {}
+	fn input(&mut self, state: &mut State, input: &Input, graphics: &mut GraphicsContext) {}
+
+	/// Return `false` to keep the window open, e.g. to ask about unsaved changes first.
+	fn close_requested(&mut self, state: &mut State, graphics: &mut GraphicsContext) -> bool { true }
+	fn focus(&mut self, state: &mut State, focused: bool, graphics: &mut GraphicsContext) {}
+	fn minimize(&mut self, state: &mut State, minimized: bool, graphics: &mut GraphicsContext) {}
+	fn exit(&mut self, state: &mut State, graphics: &mut GraphicsContext) {}
+}
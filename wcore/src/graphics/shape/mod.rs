@@ -0,0 +1,264 @@
+mod tessellate;
+
+use cgmath::{vec2, InnerSpace, Vector2, Vector4};
+
+use self::tessellate::{Geometry, Colors, Tessellator};
+
+use super::{
+    context::GraphicsContext, scene::Scene, drawable::Drawable,
+    pipeline::{shape::ShapePipeline, Pipeline, shader::scene::SceneSlot},
+    primitive::{rect::Rect, mesh::{index::Indices, data::shape::ShapeVertex}},
+};
+
+/// Fill color of a shape, evaluated at every vertex and interpolated in between.
+/// Gradients are exact along straight edges and approximated by the tessellation elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Paint {
+    Solid(Vector4<f32>),
+    /// Blends from `from` at `start` to `to` at `end`, clamped beyond them.
+    Linear { start: Vector2<f32>, end: Vector2<f32>, from: Vector4<f32>, to: Vector4<f32> },
+    /// Blends from `inner` at `center` to `outer` at `radius`, clamped beyond it.
+    Radial { center: Vector2<f32>, radius: f32, inner: Vector4<f32>, outer: Vector4<f32> },
+}
+
+impl Paint {
+    pub fn at(&self, position: Vector2<f32>) -> Vector4<f32> {
+        let (t, from, to) = match *self {
+            Paint::Solid(color) => return color,
+            Paint::Linear { start, end, from, to } => {
+                let axis = end - start;
+                ((position - start).dot(axis) / axis.magnitude2().max(f32::EPSILON), from, to)
+            }
+
+            Paint::Radial { center, radius, inner, outer } => ((position - center).magnitude() / radius.max(f32::EPSILON), inner, outer),
+        };
+
+        return from + (to - from) * t.clamp(0.0, 1.0);
+    }
+}
+
+impl From<Vector4<f32>> for Paint {
+    fn from(color: Vector4<f32>) -> Self {
+        return Paint::Solid(color);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Sharp corners, beveled once they get longer than `Stroke::miter_limit` times the width.
+    Miter,
+    Bevel,
+    Round,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineCap {
+    /// Ends exactly at the end points.
+    Butt,
+    /// Extends past the end points by half the width.
+    Square,
+    Round,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stroke {
+    /// In scene units, centered on the outline.
+    pub width       : f32,
+    pub paint       : Paint,
+    pub join        : LineJoin,
+    pub cap         : LineCap,
+    /// Ratio of miter length to stroke width above which miter joins are beveled.
+    pub miter_limit : f32,
+}
+
+impl Stroke {
+    pub fn new(width: f32, paint: impl Into<Paint>) -> Self {
+        return Self {
+            width,
+            paint       : paint.into(),
+            join        : LineJoin::Miter,
+            cap         : LineCap::Butt,
+            miter_limit : 4.0,
+        };
+    }
+
+    pub fn join(self, join: LineJoin) -> Self {
+        return Self { join, .. self };
+    }
+
+    pub fn cap(self, cap: LineCap) -> Self {
+        return Self { cap, .. self };
+    }
+}
+
+/// Immediate-mode renderer for filled and stroked 2D shapes, in scene units with y pointing down.
+/// Shapes queued during a frame are tessellated into one mesh and drawn in a single call, in submission order.
+/// Edges are anti-aliased by a fringe one physical pixel wide, so MSAA is not needed.
+pub struct ShapeRenderer {
+    scale_factor : f64,
+    pipeline     : ShapePipeline,
+
+    geometry        : Geometry,
+    vertex_buffer   : wgpu::Buffer,
+    vertex_capacity : usize,
+    index_buffer    : wgpu::Buffer,
+    /// In bytes, the index format changes with the vertex count.
+    index_capacity  : usize,
+    index_format    : wgpu::IndexFormat,
+    index_count     : u32,
+}
+
+impl ShapeRenderer {
+    pub fn new(graphics: &GraphicsContext, scene: &impl Scene) -> Self {
+        let pipeline = ShapePipeline::new(graphics, scene, false);
+        pipeline.update(&graphics.queue, scene);
+
+        return Self {
+            scale_factor: graphics.scale_factor,
+            pipeline,
+
+            geometry        : Geometry::default(),
+            vertex_buffer   : Self::make_buffer(&graphics.device, "Shape Vertices", wgpu::BufferUsages::VERTEX, std::mem::size_of::<ShapeVertex>()),
+            vertex_capacity : 1,
+            index_buffer    : Self::make_buffer(&graphics.device, "Shape Indices", wgpu::BufferUsages::INDEX, 4),
+            index_capacity  : 4,
+            index_format    : wgpu::IndexFormat::Uint16,
+            index_count     : 0,
+        };
+    }
+
+    pub fn rescale(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    pub fn fill_rect(&mut self, rect: Rect, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        self.tessellator(Colors::Paint(&paint)).fill_convex(&corners(rect));
+    }
+
+    pub fn stroke_rect(&mut self, rect: Rect, stroke: &Stroke) {
+        self.tessellator(Colors::Paint(&stroke.paint)).stroke(&corners(rect), true, stroke);
+    }
+
+    pub fn fill_rounded_rect(&mut self, rect: Rect, radius: f32, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        let tessellator = self.tessellator(Colors::Paint(&paint));
+        let points = tessellator.rounded_rect(rect, radius);
+        tessellator.fill_convex(&points);
+    }
+
+    pub fn stroke_rounded_rect(&mut self, rect: Rect, radius: f32, stroke: &Stroke) {
+        let tessellator = self.tessellator(Colors::Paint(&stroke.paint));
+        let points = tessellator.rounded_rect(rect, radius);
+        tessellator.stroke(&points, true, stroke);
+    }
+
+    pub fn fill_circle(&mut self, center: Vector2<f32>, radius: f32, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        let tessellator = self.tessellator(Colors::Paint(&paint));
+        let points = tessellator.circle(center, radius);
+        tessellator.fill_convex(&points);
+    }
+
+    pub fn stroke_circle(&mut self, center: Vector2<f32>, radius: f32, stroke: &Stroke) {
+        let tessellator = self.tessellator(Colors::Paint(&stroke.paint));
+        let points = tessellator.circle(center, radius);
+        tessellator.stroke(&points, true, stroke);
+    }
+
+    pub fn line(&mut self, from: Vector2<f32>, to: Vector2<f32>, stroke: &Stroke) {
+        self.polyline(&[from, to], false, stroke);
+    }
+
+    pub fn polyline(&mut self, points: &[Vector2<f32>], closed: bool, stroke: &Stroke) {
+        self.tessellator(Colors::Paint(&stroke.paint)).stroke(points, closed, stroke);
+    }
+
+    /// Like `polyline` with one color per point instead of the stroke's paint, blended along the segments.
+    pub fn polyline_colored(&mut self, points: &[Vector2<f32>], colors: &[Vector4<f32>], closed: bool, stroke: &Stroke) {
+        assert_eq!(points.len(), colors.len(), "polyline_colored needs one color per point");
+        self.tessellator(Colors::Points(colors)).stroke(points, closed, stroke);
+    }
+
+    /// The polygon must be convex, its winding does not matter.
+    pub fn fill_polygon(&mut self, points: &[Vector2<f32>], paint: impl Into<Paint>) {
+        let paint = paint.into();
+        self.tessellator(Colors::Paint(&paint)).fill_convex(points);
+    }
+
+    /// Like `fill_polygon` with one color per point, blended across the polygon.
+    pub fn fill_polygon_colored(&mut self, points: &[Vector2<f32>], colors: &[Vector4<f32>]) {
+        assert_eq!(points.len(), colors.len(), "fill_polygon_colored needs one color per point");
+        self.tessellator(Colors::Points(colors)).fill_convex(points);
+    }
+
+    /// Uploads the queued shapes, clearing the queue for the next frame.
+    pub fn prepare(&mut self, graphics: &GraphicsContext) {
+        let Geometry { vertices, indices } = std::mem::take(&mut self.geometry);
+
+        if vertices.len() > self.vertex_capacity {
+            self.vertex_capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = Self::make_buffer(&graphics.device, "Shape Vertices", wgpu::BufferUsages::VERTEX, self.vertex_capacity * std::mem::size_of::<ShapeVertex>());
+        }
+
+        let index_size = match indices {
+            Indices::U16(_) => indices.len() * 2,
+            Indices::U32(_) => indices.len() * 4,
+        }.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize);
+
+        if index_size > self.index_capacity {
+            self.index_capacity = index_size.next_power_of_two();
+            self.index_buffer = Self::make_buffer(&graphics.device, "Shape Indices", wgpu::BufferUsages::INDEX, self.index_capacity);
+        }
+
+        self.index_count = indices.len() as u32;
+        self.index_format = indices.format();
+        if !indices.is_empty() {
+            graphics.queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+            indices.write(&graphics.queue, &self.index_buffer);
+        }
+    }
+
+    fn tessellator<'a>(&'a mut self, colors: Colors<'a>) -> Tessellator<'a> {
+        // Fringe and curve tolerance follow physical pixels
+        let pixel = 1.0 / self.scale_factor as f32;
+        return Tessellator::new(&mut self.geometry, colors, pixel, pixel / 4.0);
+    }
+
+    fn make_buffer(device: &wgpu::Device, label: &str, usage: wgpu::BufferUsages, size: usize) -> wgpu::Buffer {
+        return device.create_buffer(&wgpu::BufferDescriptor {
+            label              : Some(label),
+            size               : size.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize) as wgpu::BufferAddress,
+            usage              : usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation : false,
+        });
+    }
+}
+
+impl Drawable for ShapeRenderer {
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.index_count == 0 {
+            return;
+        }
+
+        self.pipeline.attach(render_pass);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+        render_pass.draw_indexed(0 .. self.index_count, 0, 0 .. 1);
+    }
+}
+
+unsafe impl SceneSlot for ShapeRenderer {
+    fn update(&self, queue: &wgpu::Queue, scene: &impl Scene) {
+        self.pipeline.update(queue, scene);
+    }
+}
+
+fn corners(rect: Rect) -> [Vector2<f32>; 4] {
+    return [
+        vec2(rect.x, rect.y),
+        vec2(rect.right(), rect.y),
+        vec2(rect.right(), rect.bottom()),
+        vec2(rect.x, rect.bottom()),
+    ];
+}
\ No newline at end of file
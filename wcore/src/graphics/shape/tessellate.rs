@@ -0,0 +1,535 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use cgmath::{vec2, InnerSpace, Vector2, Vector4};
+
+use crate::graphics::primitive::{rect::Rect, mesh::{index::Indices, data::shape::ShapeVertex}, geometry::{arc_segments, cross, perpendicular, EPSILON}};
+
+use super::{LineCap, LineJoin, Paint, Stroke};
+
+/// Corners of filled polygons sharper than this are cut off, so the feather does not spike out.
+const FILL_MITER_LIMIT: f32 = 4.0;
+
+/// Everything queued since the last upload.
+pub(super) struct Geometry {
+    pub vertices : Vec<ShapeVertex>,
+    pub indices  : Indices,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        return Self {
+            vertices : vec![],
+            indices  : Indices::U16(vec![]),
+        };
+    }
+}
+
+pub(super) enum Colors<'a> {
+    Paint(&'a Paint),
+    /// One color per input point.
+    Points(&'a [Vector4<f32>]),
+}
+
+/// Cross-section of a stroke: left edge, left core, right core, right edge, left being the normal side.
+type Section = [u32; 4];
+
+/// Distances from the center line of a stroke.
+#[derive(Clone, Copy)]
+struct Width {
+    half  : f32,
+    /// Where the core ends and the fringe starts.
+    inner : f32,
+    outer : f32,
+    /// Alpha of the core.
+    alpha : f32,
+}
+
+impl Width {
+    fn faded(self) -> Self {
+        return Self { alpha: 0.0, .. self };
+    }
+}
+
+/// Vertices of a fan where joins and caps connect to the stroke, as core and edge pairs.
+struct Fan {
+    center : u32,
+    first  : (u32, u32),
+    last   : (u32, u32),
+}
+
+/// Writes one shape into `Geometry`. Indices are relative to the shape until `finish` offsets them.
+///
+/// Edges are anti-aliased by feathering: every outline is split into a core that is fully opaque and a fringe
+/// one `feather` wide, centered on the outline, whose alpha fades to zero.
+pub(super) struct Tessellator<'a> {
+    geometry  : &'a mut Geometry,
+    colors    : Colors<'a>,
+    feather   : f32,
+    /// Maximum distance between curves and their segments.
+    tolerance : f32,
+
+    base    : u32,
+    indices : Vec<u32>,
+}
+
+impl<'a> Tessellator<'a> {
+    pub fn new(geometry: &'a mut Geometry, colors: Colors<'a>, feather: f32, tolerance: f32) -> Self {
+        let base = geometry.vertices.len() as u32;
+        return Self {
+            geometry,
+            colors,
+            feather,
+            tolerance,
+
+            base,
+            indices: vec![],
+        };
+    }
+
+    /// Fills a convex polygon in either winding, fanned out from its centroid so radial gradients have a center.
+    pub fn fill_convex(mut self, points: &[Vector2<f32>]) {
+        let points = dedup(points, true);
+        let count = points.len();
+        let area = (0 .. count).map(|i| cross(points[i].1, points[(i + 1) % count].1)).sum::<f32>();
+        if count < 3 || area.abs() < EPSILON {
+            return self.finish();
+        }
+
+        // Edge normals point outwards regardless of winding
+        let side = -area.signum();
+        let normal = |a: Vector2<f32>, b: Vector2<f32>| perpendicular((b - a).normalize()) * side;
+        let half = self.feather / 2.0;
+
+        let mut ring = Vec::with_capacity(count);
+        for i in 0 .. count {
+            let (point, position) = points[i];
+            let n0 = normal(points[(i + count - 1) % count].1, position);
+            let n1 = normal(position, points[(i + 1) % count].1);
+            let m = miter(n0, n1).unwrap_or(n0);
+            let m = m * (FILL_MITER_LIMIT / m.magnitude()).min(1.0);
+
+            ring.push((self.vertex(point, position - m * half, 1.0), self.vertex(point, position + m * half, 0.0)));
+        }
+
+        let centroid = points.iter().map(|(_, position)| position).sum::<Vector2<f32>>() / count as f32;
+        let color = match self.colors {
+            Colors::Paint(paint)   => paint.at(centroid),
+            Colors::Points(colors) => points.iter().map(|&(point, _)| colors[point]).sum::<Vector4<f32>>() / count as f32,
+        };
+
+        let center = self.push(centroid, color);
+        for i in 0 .. count {
+            let (core, edge) = ring[i];
+            let (next_core, next_edge) = ring[(i + 1) % count];
+
+            self.triangle(center, core, next_core);
+            self.quad(core, edge, next_edge, next_core);
+        }
+
+        self.finish();
+    }
+
+    /// Strokes a polyline, closed ones are joined back to the first point instead of capped.
+    pub fn stroke(mut self, points: &[Vector2<f32>], closed: bool, stroke: &Stroke) {
+        let points = dedup(points, closed);
+        let count = points.len();
+        let closed = closed && count > 2;
+        if count < 2 || stroke.width <= 0.0 {
+            return self.finish();
+        }
+
+        // Lines thinner than the feather have no core, they fade out by coverage instead
+        let half = stroke.width / 2.0;
+        let width = Width {
+            half,
+            inner : (half - self.feather / 2.0).max(0.0),
+            outer : half + self.feather / 2.0,
+            alpha : (stroke.width / self.feather).min(1.0),
+        };
+
+        let segments = if closed { count } else { count - 1 };
+        let vectors = (0 .. segments).map(|i| points[(i + 1) % count].1 - points[i].1).collect::<Vec<_>>();
+
+        // Every point ends the segment before it and starts the one after it
+        let mut ends = vec![[0; 4]; count];
+        let mut starts = vec![[0; 4]; count];
+        for i in 0 .. count {
+            let (point, position) = points[i];
+
+            if !closed && i == 0 {
+                let direction = vectors[0].normalize();
+                starts[i] = self.cap(point, position, direction, -direction, stroke.cap, width);
+            } else if !closed && i == count - 1 {
+                let direction = vectors[segments - 1].normalize();
+                ends[i] = self.cap(point, position, direction, direction, stroke.cap, width);
+            } else {
+                let incoming = vectors[(i + segments - 1) % segments];
+                let outgoing = vectors[i];
+                (ends[i], starts[i]) = self.join(point, position, incoming, outgoing, stroke, width);
+            }
+        }
+
+        for i in 0 .. segments {
+            self.strip(starts[i], ends[(i + 1) % count]);
+        }
+
+        self.finish();
+    }
+
+    fn join(&mut self, point: usize, position: Vector2<f32>, incoming: Vector2<f32>, outgoing: Vector2<f32>, stroke: &Stroke, width: Width) -> (Section, Section) {
+        let (d0, d1) = (incoming.normalize(), outgoing.normalize());
+        let (n0, n1) = (perpendicular(d0), perpendicular(d1));
+
+        let turn = cross(d0, d1);
+        if turn.abs() < EPSILON && d0.dot(d1) > 0.0 {
+            let section = self.section(point, position, n0, width);
+            return (section, section);
+        }
+
+        // The side the path turns towards overlaps itself, both segments meet at the miter point there.
+        // It is pulled in on short segments, where it would reach past the neighbouring points.
+        let side = turn.signum();
+        let m = miter(n0, n1).unwrap_or(n0);
+        let reach = incoming.magnitude().min(outgoing.magnitude()) / width.outer;
+        let overlap = m * (reach / m.magnitude()).min(1.0) * side;
+        let overlap = (self.vertex(point, position + overlap * width.inner, width.alpha), self.vertex(point, position + overlap * width.outer, 0.0));
+
+        let sections = |this: (u32, u32), other: (u32, u32)| match side > 0.0 {
+            true  => [other.1, other.0, this.0, this.1],
+            false => [this.1, this.0, other.0, other.1],
+        };
+
+        if stroke.join == LineJoin::Miter && m.magnitude() <= stroke.miter_limit {
+            let m = m * -side;
+            let spike = (self.vertex(point, position + m * width.inner, width.alpha), self.vertex(point, position + m * width.outer, 0.0));
+            let section = sections(spike, overlap);
+            return (section, section);
+        }
+
+        // Bevels are round joins with a single segment
+        let from = n0 * -side;
+        let to = n1 * -side;
+        let sweep = cross(from, to).atan2(from.dot(to));
+        let steps = match stroke.join {
+            LineJoin::Round => arc_segments(width.outer, sweep, self.tolerance),
+            _               => 1,
+        };
+
+        let fan = self.fan(point, position, from, sweep, steps, width);
+        self.triangle(overlap.0, fan.first.0, fan.center);
+        self.triangle(overlap.0, fan.center, fan.last.0);
+
+        return (sections(fan.first, overlap), sections(fan.last, overlap));
+    }
+
+    /// `outwards` points away from the line, `direction` is the direction of the segment at the end.
+    fn cap(&mut self, point: usize, position: Vector2<f32>, direction: Vector2<f32>, outwards: Vector2<f32>, cap: LineCap, width: Width) -> Section {
+        let normal = perpendicular(direction);
+        let fringe = self.feather / 2.0;
+
+        let position = match cap {
+            LineCap::Square => position + outwards * width.half,
+            _               => position,
+        };
+
+        if cap == LineCap::Round {
+            // Half a turn from the left edge around the end to the right edge
+            let sweep = if outwards.dot(direction) < 0.0 { PI } else { -PI };
+            let steps = arc_segments(width.outer, PI, self.tolerance);
+            let fan = self.fan(point, position, normal, sweep, steps, width);
+
+            return [fan.first.1, fan.first.0, fan.last.0, fan.last.1];
+        }
+
+        // The core stops half a feather short of the end and fades out over the other half
+        let section = self.section(point, position - outwards * fringe, normal, width);
+        let end = self.section(point, position + outwards * fringe, normal, width.faded());
+        self.strip(section, end);
+
+        return section;
+    }
+
+    fn section(&mut self, point: usize, position: Vector2<f32>, normal: Vector2<f32>, width: Width) -> Section {
+        return [
+            self.vertex(point, position + normal * width.outer, 0.0),
+            self.vertex(point, position + normal * width.inner, width.alpha),
+            self.vertex(point, position - normal * width.inner, width.alpha),
+            self.vertex(point, position - normal * width.outer, 0.0),
+        ];
+    }
+
+    /// Connects two sections with quads, the core in the middle and the fringe on either side.
+    fn strip(&mut self, a: Section, b: Section) {
+        for i in 0 .. 3 {
+            self.quad(a[i], a[i + 1], b[i + 1], b[i]);
+        }
+    }
+
+    /// Sector around `center` starting at the unit vector `from`, turning `sweep` radians in `steps` segments.
+    fn fan(&mut self, point: usize, center: Vector2<f32>, from: Vector2<f32>, sweep: f32, steps: u32, width: Width) -> Fan {
+        let origin = center;
+        let center = self.vertex(point, origin, width.alpha);
+
+        let mut ring = Vec::with_capacity(steps as usize + 1);
+        for step in 0 ..= steps {
+            let direction = rotate(from, sweep * step as f32 / steps as f32);
+            ring.push((self.vertex(point, origin + direction * width.inner, width.alpha), self.vertex(point, origin + direction * width.outer, 0.0)));
+        }
+
+        for pair in ring.windows(2) {
+            let ((core, edge), (next_core, next_edge)) = (pair[0], pair[1]);
+            self.triangle(center, core, next_core);
+            self.quad(core, edge, next_edge, next_core);
+        }
+
+        return Fan {
+            center,
+            first : ring[0],
+            last  : ring[ring.len() - 1],
+        };
+    }
+
+    pub fn circle(&self, center: Vector2<f32>, radius: f32) -> Vec<Vector2<f32>> {
+        let steps = arc_segments(radius, TAU, self.tolerance).max(8);
+        return (0 .. steps).map(|step| center + rotate(vec2(radius, 0.0), TAU * step as f32 / steps as f32)).collect();
+    }
+
+    /// Radius is clamped to half the shorter side.
+    pub fn rounded_rect(&self, rect: Rect, radius: f32) -> Vec<Vector2<f32>> {
+        let radius = radius.min(rect.width / 2.0).min(rect.height / 2.0).max(0.0);
+        let corners = [
+            (vec2(rect.x + radius, rect.y + radius), PI),
+            (vec2(rect.right() - radius, rect.y + radius), PI * 1.5),
+            (vec2(rect.right() - radius, rect.bottom() - radius), 0.0),
+            (vec2(rect.x + radius, rect.bottom() - radius), FRAC_PI_2),
+        ];
+
+        if radius <= 0.0 {
+            return corners.iter().map(|&(corner, _)| corner).collect();
+        }
+
+        let steps = arc_segments(radius, FRAC_PI_2, self.tolerance);
+        let mut points = Vec::with_capacity(4 * (steps as usize + 1));
+        for (center, start) in corners {
+            for step in 0 ..= steps {
+                let angle = start + FRAC_PI_2 * step as f32 / steps as f32;
+                points.push(center + vec2(angle.cos(), angle.sin()) * radius);
+            }
+        }
+
+        return points;
+    }
+
+    fn vertex(&mut self, point: usize, position: Vector2<f32>, alpha: f32) -> u32 {
+        let color = match self.colors {
+            Colors::Paint(paint)   => paint.at(position),
+            Colors::Points(colors) => colors[point],
+        };
+
+        return self.push(position, Vector4 { w: color.w * alpha, .. color });
+    }
+
+    fn push(&mut self, position: Vector2<f32>, color: Vector4<f32>) -> u32 {
+        self.geometry.vertices.push(ShapeVertex { pos: position, color });
+        return self.geometry.vertices.len() as u32 - 1 - self.base;
+    }
+
+    fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.indices.extend([a, b, c]);
+    }
+
+    fn quad(&mut self, a: u32, b: u32, c: u32, d: u32) {
+        self.indices.extend([a, b, c, c, d, a]);
+    }
+
+    fn finish(self) {
+        self.geometry.indices.extend(self.indices, self.base);
+    }
+}
+
+/// Drops repeated points, keeping the index of the first so per-point colors still line up.
+fn dedup(points: &[Vector2<f32>], closed: bool) -> Vec<(usize, Vector2<f32>)> {
+    let mut unique: Vec<(usize, Vector2<f32>)> = Vec::with_capacity(points.len());
+    for (i, &point) in points.iter().enumerate() {
+        if unique.last().is_none_or(|&(_, last)| (point - last).magnitude() > EPSILON) {
+            unique.push((i, point));
+        }
+    }
+
+    if closed && unique.len() > 1 && (unique[0].1 - unique[unique.len() - 1].1).magnitude() <= EPSILON {
+        unique.pop();
+    }
+
+    return unique;
+}
+
+/// Offset that moves both edges meeting at a corner by one unit, `None` if they fold back onto each other.
+fn miter(n0: Vector2<f32>, n1: Vector2<f32>) -> Option<Vector2<f32>> {
+    let scale = 1.0 + n0.dot(n1);
+    if scale < EPSILON {
+        return None;
+    }
+
+    return Some((n0 + n1) / scale);
+}
+
+fn rotate(v: Vector2<f32>, angle: f32) -> Vector2<f32> {
+    let (sin, cos) = angle.sin_cos();
+    return vec2(v.x * cos - v.y * sin, v.x * sin + v.y * cos);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Paint = Paint::Solid(Vector4 { x: 1.0, y: 1.0, z: 1.0, w: 1.0 });
+    const FEATHER: f32 = 1.0;
+    const TOLERANCE: f32 = 0.25;
+
+    fn tessellator(geometry: &mut Geometry) -> Tessellator<'_> {
+        return Tessellator::new(geometry, Colors::Paint(&WHITE), FEATHER, TOLERANCE);
+    }
+
+    fn fill(points: &[Vector2<f32>]) -> Geometry {
+        let mut geometry = Geometry::default();
+        tessellator(&mut geometry).fill_convex(points);
+        return geometry;
+    }
+
+    fn stroke(points: &[Vector2<f32>], closed: bool, stroke: &Stroke) -> Geometry {
+        let mut geometry = Geometry::default();
+        tessellator(&mut geometry).stroke(points, closed, stroke);
+        return geometry;
+    }
+
+    fn square() -> Vec<Vector2<f32>> {
+        return vec![vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0), vec2(0.0, 10.0)];
+    }
+
+    fn indices(geometry: &Geometry) -> Vec<u32> {
+        return match &geometry.indices {
+            Indices::U16(indices) => indices.iter().map(|&index| index as u32).collect(),
+            Indices::U32(indices) => indices.clone(),
+        };
+    }
+
+    fn positions(geometry: &Geometry) -> Vec<Vector2<f32>> {
+        return geometry.vertices.iter().map(|vertex| vertex.pos).collect();
+    }
+
+    /// Whole triangles that only point at existing vertices.
+    fn assert_valid(geometry: &Geometry) {
+        let indices = indices(geometry);
+        assert_eq!(indices.len() % 3, 0);
+        assert!(indices.iter().all(|&index| (index as usize) < geometry.vertices.len()), "{indices:?}");
+    }
+
+    #[test]
+    fn fills_rect() {
+        // Core and edge per corner plus the centroid, a triangle and a fringe quad per side
+        let geometry = fill(&square());
+        assert_eq!(geometry.vertices.len(), 9);
+        assert_eq!(geometry.indices.len(), 36);
+        assert_valid(&geometry);
+    }
+
+    #[test]
+    fn fills_circle() {
+        let mut geometry = Geometry::default();
+        let shape = tessellator(&mut geometry);
+        let points = shape.circle(vec2(5.0, 5.0), 10.0);
+        assert!(points.iter().all(|point| ((point - vec2(5.0, 5.0)).magnitude() - 10.0).abs() < 1e-3));
+        shape.fill_convex(&points);
+
+        assert_eq!(geometry.vertices.len(), points.len() * 2 + 1);
+        assert_eq!(geometry.indices.len(), points.len() * 9);
+        assert_valid(&geometry);
+
+        // Small circles still get enough points to look round
+        assert_eq!(tessellator(&mut Geometry::default()).circle(vec2(0.0, 0.0), 0.1).len(), 8);
+    }
+
+    #[test]
+    fn strokes_polylines() {
+        // Butt caps are a section and a faded one joined by a strip, the segment between them is another strip
+        let line = stroke(&[vec2(0.0, 0.0), vec2(10.0, 0.0)], false, &Stroke::new(2.0, WHITE));
+        assert_eq!(line.vertices.len(), 16);
+        assert_eq!(line.indices.len(), 54);
+        assert_valid(&line);
+
+        // A miter join adds the overlap and the spike, core and edge each
+        let corner = stroke(&[vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)], false, &Stroke::new(2.0, WHITE));
+        assert_eq!(corner.vertices.len(), 20);
+        assert_eq!(corner.indices.len(), 72);
+        assert_valid(&corner);
+
+        for join in [LineJoin::Miter, LineJoin::Bevel, LineJoin::Round] {
+            for cap in [LineCap::Butt, LineCap::Square, LineCap::Round] {
+                let style = Stroke::new(3.0, WHITE).join(join).cap(cap);
+                assert_valid(&stroke(&[vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0), vec2(0.0, 0.5)], false, &style));
+                assert_valid(&stroke(&square(), true, &style));
+            }
+        }
+    }
+
+    #[test]
+    fn later_shapes_index_their_own_vertices() {
+        let mut geometry = Geometry::default();
+        tessellator(&mut geometry).fill_convex(&square());
+        tessellator(&mut geometry).stroke(&square(), true, &Stroke::new(2.0, WHITE));
+
+        let indices = indices(&geometry);
+        assert!(indices[36 ..].iter().all(|&index| index >= 9));
+        assert_valid(&geometry);
+    }
+
+    #[test]
+    fn fringe_fades_out() {
+        let geometry = fill(&square());
+        for vertex in &geometry.vertices {
+            let outside = vertex.pos.x < 0.0 || vertex.pos.x > 10.0 || vertex.pos.y < 0.0 || vertex.pos.y > 10.0;
+            assert_eq!(vertex.color.w, if outside { 0.0 } else { 1.0 }, "{:?}", vertex.pos);
+        }
+
+        // Anything past the stroke's half width is fringe
+        let geometry = stroke(&[vec2(0.0, 0.0), vec2(10.0, 0.0)], false, &Stroke::new(4.0, WHITE).cap(LineCap::Round));
+        assert!(geometry.vertices.iter().any(|vertex| vertex.color.w == 1.0));
+        for vertex in &geometry.vertices {
+            if (vertex.pos - vec2(vertex.pos.x.clamp(0.0, 10.0), 0.0)).magnitude() > 2.0 {
+                assert_eq!(vertex.color.w, 0.0, "{:?}", vertex.pos);
+            }
+        }
+
+        // Lines thinner than the feather fade by coverage
+        let geometry = stroke(&[vec2(0.0, 0.0), vec2(10.0, 0.0)], false, &Stroke::new(0.5, WHITE));
+        assert_eq!(geometry.vertices.iter().map(|vertex| vertex.color.w).fold(0.0, f32::max), 0.5);
+    }
+
+    #[test]
+    fn skips_degenerate_input() {
+        let point = vec2(3.0, 4.0);
+        assert!(stroke(&[point, point], false, &Stroke::new(2.0, WHITE)).vertices.is_empty());
+        assert!(stroke(&[point, point + vec2(1e-5, 0.0)], false, &Stroke::new(2.0, WHITE)).vertices.is_empty());
+        assert!(stroke(&[vec2(0.0, 0.0), vec2(10.0, 0.0)], false, &Stroke::new(0.0, WHITE)).vertices.is_empty());
+        assert!(fill(&[point, point, point]).vertices.is_empty());
+        assert!(fill(&[vec2(0.0, 0.0), vec2(5.0, 0.0), vec2(10.0, 0.0)]).vertices.is_empty());
+
+        let empty = fill(&[]);
+        assert!(empty.vertices.is_empty() && empty.indices.is_empty());
+    }
+
+    #[test]
+    fn merges_repeated_points() {
+        let path = [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)];
+        let repeated = [vec2(0.0, 0.0), vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)];
+        let (a, b) = (stroke(&path, false, &Stroke::new(2.0, WHITE)), stroke(&repeated, false, &Stroke::new(2.0, WHITE)));
+        assert_eq!(positions(&a), positions(&b));
+        assert_eq!(indices(&a), indices(&b));
+
+        // A closing point equal to the first does not add a zero-length side
+        let mut closed = square();
+        closed.push(closed[0]);
+        assert_eq!(positions(&fill(&closed)), positions(&fill(&square())));
+        assert_eq!(positions(&stroke(&closed, true, &Stroke::new(2.0, WHITE))), positions(&stroke(&square(), true, &Stroke::new(2.0, WHITE))));
+    }
+}
\ No newline at end of file
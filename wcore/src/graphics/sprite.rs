@@ -0,0 +1,143 @@
+use std::{ops::Range, sync::Arc};
+
+use cgmath::{Vector2, Vector4};
+
+use super::{
+    context::GraphicsContext, scene::Scene, texture::Texture, bindable::Bindable,
+    pipeline::{sprite::SpritePipeline, Pipeline, shader::scene::SceneSlot},
+    primitive::{rect::Rect, mesh::{simple::Mesh, instanced::Instance, index::Indices, data::{sprite::{Sprite, SpriteRaw}, vertex::Vertex}}},
+};
+
+/// Consecutive sprites sharing a texture and pipeline, drawn with one call.
+struct Batch {
+    texture  : Arc<Texture>,
+    pipeline : Arc<dyn Pipeline>,
+    range    : Range<u32>,
+}
+
+/// Immediate-mode 2D sprite renderer.
+/// Sprites are drawn in submission order, consecutive sprites with the same texture and pipeline share a draw call.
+/// Custom pipelines have to use the `SpriteRaw` instance layout and take the texture at bind group 1.
+pub struct SpriteBatch {
+    default  : Arc<SpritePipeline>,
+    pipeline : Arc<dyn Pipeline>,
+
+    quad              : wgpu::Buffer,
+    quad_indices      : wgpu::Buffer,
+    instance_buffer   : wgpu::Buffer,
+    instance_capacity : usize,
+
+    sprites : Vec<SpriteRaw>,
+    batches : Vec<Batch>,
+    /// The last flush is still referenced by its render pass, cleared on the next draw.
+    flushed : bool,
+}
+
+impl SpriteBatch {
+    pub fn new(graphics: &GraphicsContext, scene: &impl Scene) -> Self {
+        let default = Arc::new(SpritePipeline::new(graphics, scene, false));
+        default.update(&graphics.queue, scene);
+
+        let (vertices, indices) = Vertex::indexed_rect(0.0, 1.0);
+        return Self {
+            pipeline: default.clone(),
+            default,
+
+            quad              : Mesh::make_buffer(&graphics.device, &vertices),
+            quad_indices      : Indices::from(indices).make_buffer(&graphics.device),
+            instance_buffer   : Self::make_instance_buffer(&graphics.device, 1),
+            instance_capacity : 1,
+
+            sprites : vec![],
+            batches : vec![],
+            flushed : false,
+        };
+    }
+
+    /// Used for the following draws until the next flush.
+    pub fn set_pipeline(&mut self, pipeline: Arc<dyn Pipeline>) {
+        self.pipeline = pipeline;
+    }
+
+    pub fn reset_pipeline(&mut self) {
+        self.pipeline = self.default.clone();
+    }
+
+    /// Queues a sprite, `rotation` is clockwise in radians around `origin`, which is relative to the top left of `rect`.
+    pub fn draw(&mut self, texture: &Arc<Texture>, rect: Rect, uv_rect: Rect, color: Vector4<f32>, rotation: f32, origin: Vector2<f32>) {
+        if self.flushed {
+            self.sprites.clear();
+            self.batches.clear();
+            self.flushed = false;
+        }
+
+        let index = self.sprites.len() as u32;
+        self.sprites.push(Sprite { rect, uv: uv_rect, color, rotation, origin }.to_raw());
+
+        match self.batches.last_mut() {
+            Some(batch) if Arc::ptr_eq(&batch.texture, texture) && Arc::ptr_eq(&batch.pipeline, &self.pipeline) => {
+                batch.range.end = index + 1;
+            }
+
+            _ => self.batches.push(Batch {
+                texture  : texture.clone(),
+                pipeline : self.pipeline.clone(),
+                range    : index .. index + 1,
+            }),
+        }
+    }
+
+    /// Number of draw calls the next flush will issue.
+    pub fn batch_count(&self) -> usize {
+        return if self.flushed { 0 } else { self.batches.len() };
+    }
+
+    /// Uploads the queued sprites and draws them into the pass.
+    /// The instance buffer is shared, so flush at most once per submitted encoder.
+    pub fn flush<'a>(&'a mut self, device: &wgpu::Device, queue: &wgpu::Queue, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.flushed || self.sprites.is_empty() {
+            return;
+        }
+
+        if self.sprites.len() > self.instance_capacity {
+            self.instance_capacity = self.sprites.len().next_power_of_two();
+            self.instance_buffer = Self::make_instance_buffer(device, self.instance_capacity);
+        }
+
+        queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&self.sprites));
+        self.flushed = true;
+
+        render_pass.set_vertex_buffer(0, self.quad.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        render_pass.set_index_buffer(self.quad_indices.slice(..), wgpu::IndexFormat::Uint16);
+
+        let mut previous: Option<&Batch> = None;
+        for batch in &self.batches {
+            if !previous.is_some_and(|previous| Arc::ptr_eq(&previous.pipeline, &batch.pipeline)) {
+                batch.pipeline.attach(render_pass);
+            }
+
+            if !previous.is_some_and(|previous| Arc::ptr_eq(&previous.texture, &batch.texture)) {
+                batch.texture.bind(render_pass, 1);
+            }
+
+            render_pass.draw_indexed(0 .. 6, 0, batch.range.clone());
+            previous = Some(batch);
+        }
+    }
+
+    fn make_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        return device.create_buffer(&wgpu::BufferDescriptor {
+            label              : Some("Sprite Instances"),
+            size               : (capacity * std::mem::size_of::<SpriteRaw>()) as wgpu::BufferAddress,
+            usage              : wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation : false,
+        });
+    }
+}
+
+unsafe impl SceneSlot for SpriteBatch {
+    fn update(&self, queue: &wgpu::Queue, scene: &impl Scene) {
+        self.default.update(queue, scene);
+    }
+}
\ No newline at end of file
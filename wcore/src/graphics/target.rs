@@ -0,0 +1,148 @@
+use super::{context::GraphicsContext, texture::{Texture, TextureSettings}, bindable::Bindable, utils::{self, ColorTarget}};
+
+/// Offscreen color texture with an optional depth buffer.
+/// Render into it with `utils::render(encoder, target.attachment(), target.depth_view(), ..)`, then bind it like a `Texture`.
+/// With MSAA enabled in the context it also has a multisampled attachment, resolved into the texture.
+/// The color format matches the surface, so the usual pipelines can draw into it.
+pub struct RenderTarget {
+    pub texture : Texture,
+
+    depth       : Option<(wgpu::Texture, wgpu::TextureView)>,
+    msaa        : Option<(wgpu::Texture, wgpu::TextureView)>,
+    samples     : u32,
+    /// Size relative to the window, `None` for a fixed size.
+    scale       : Option<f32>,
+}
+
+impl RenderTarget {
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    /// A target with a fixed size in pixels, with `depth` only pipelines created with depth can draw into it.
+    pub fn new(graphics : &GraphicsContext,
+               width    : u32,
+               height   : u32,
+               depth    : bool,
+               settings : impl Into<TextureSettings>,
+               label    : Option<&str>) -> Self {
+        let texture = Texture::with_format(graphics, width, height, graphics.surface_configuration.format, settings, label);
+        let samples = graphics.sample_count;
+        let depth = depth.then(|| Self::make_depth(graphics, texture.size(), samples));
+        let msaa = Self::make_msaa(graphics, texture.size(), samples);
+
+        return Self { texture, depth, msaa, samples, scale: None };
+    }
+
+    /// A target that follows the window size, `scale` of 0.5 renders at half the resolution.
+    /// Call `resize_to_window` from `Screen::resize`.
+    pub fn window_sized(graphics : &GraphicsContext,
+                        scale    : f32,
+                        depth    : bool,
+                        settings : impl Into<TextureSettings>,
+                        label    : Option<&str>) -> Self {
+        let (width, height) = Self::window_size(graphics, scale);
+        let mut target = Self::new(graphics, width, height, depth, settings, label);
+        target.scale = Some(scale);
+
+        return target;
+    }
+
+    /// Drops the multisampled attachment, for targets only drawn into by single sampled pipelines
+    /// like post-processing passes.
+    pub fn single_sampled(mut self, graphics: &GraphicsContext) -> Self {
+        self.samples = 1;
+        self.msaa = None;
+        if self.depth.is_some() {
+            self.depth = Some(Self::make_depth(graphics, self.texture.size(), 1));
+        }
+
+        return self;
+    }
+
+    /// The resolved texture, single sampled pipelines can draw into it directly.
+    pub fn view(&self) -> &wgpu::TextureView {
+        return &self.texture.view;
+    }
+
+    /// Color attachment for the pipelines of the context, multisampled if MSAA is enabled.
+    pub fn attachment(&self) -> ColorTarget<'_> {
+        return match &self.msaa {
+            Some((_, msaa)) => ColorTarget { view: msaa, resolve_target: Some(&self.texture.view) },
+            None            => ColorTarget::from(&self.texture.view),
+        };
+    }
+
+    pub fn depth_view(&self) -> Option<&wgpu::TextureView> {
+        return self.depth.as_ref().map(|(_, view)| view);
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        return self.texture.size();
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        return self.texture.format();
+    }
+
+    /// Resizes to a fixed size, the target stops following the window.
+    pub fn resize(&mut self, graphics: &GraphicsContext, width: u32, height: u32) {
+        self.scale = None;
+        self.set_size(graphics, (width, height));
+    }
+
+    /// Matches the current window size if the target was created with `window_sized`.
+    pub fn resize_to_window(&mut self, graphics: &GraphicsContext) {
+        if let Some(scale) = self.scale {
+            self.set_size(graphics, Self::window_size(graphics, scale));
+        }
+    }
+
+    fn set_size(&mut self, graphics: &GraphicsContext, size: (u32, u32)) {
+        if size == self.size() {
+            return;
+        }
+
+        self.texture.resize(graphics, size.0, size.1);
+        if self.depth.is_some() {
+            self.depth = Some(Self::make_depth(graphics, self.texture.size(), self.samples));
+        }
+
+        self.msaa = Self::make_msaa(graphics, self.texture.size(), self.samples);
+    }
+
+    fn window_size(graphics: &GraphicsContext, scale: f32) -> (u32, u32) {
+        let width = (graphics.surface_configuration.width as f32 * scale).round() as u32;
+        let height = (graphics.surface_configuration.height as f32 * scale).round() as u32;
+
+        return (width.max(1), height.max(1));
+    }
+
+    fn make_depth(graphics: &GraphicsContext, size: (u32, u32), samples: u32) -> (wgpu::Texture, wgpu::TextureView) {
+        return utils::attachment(&graphics.device, size, Self::DEPTH_FORMAT, samples, "Render Target Depth");
+    }
+
+    fn make_msaa(graphics: &GraphicsContext, size: (u32, u32), samples: u32) -> Option<(wgpu::Texture, wgpu::TextureView)> {
+        if samples <= 1 {
+            return None;
+        }
+
+        return Some(utils::attachment(&graphics.device, size, graphics.surface_configuration.format, samples, "Render Target Multisampled"));
+    }
+}
+
+impl Bindable for RenderTarget {
+    fn bind<'a, 'b: 'a>(&'b self, render_pass: &mut wgpu::RenderPass<'a>, index: u32) {
+        self.texture.bind(render_pass, index);
+    }
+
+    fn layout(&self) -> &wgpu::BindGroupLayout {
+        return self.texture.layout();
+    }
+
+    fn layout_entries(&self) -> &'static [wgpu::BindGroupLayoutEntry] {
+        return self.texture.layout_entries();
+    }
+
+    fn group(&self) -> &wgpu::BindGroup {
+        return self.texture.group();
+    }
+}
\ No newline at end of file
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use image::{DynamicImage, RgbaImage, Rgba};
+use log::warn;
+
+use crate::graphics::{context::GraphicsContext, texture::Texture};
+
+use super::font::Font;
+
+const WIDTH          : u32 = 1024;
+const INITIAL_HEIGHT : u32 = 256;
+
+/// Transparent border around every glyph, keeps linear filtering from bleeding into neighbours.
+const PADDING        : u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct GlyphKey {
+    pub font  : usize,
+    pub index : u16,
+    /// Bits of the rasterized pixel size, sizes are never NaN.
+    pub px    : u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct AtlasGlyph {
+    /// Offset of the bitmap's top left corner from the pen position on the baseline.
+    pub offset : (f32, f32),
+    /// x, y, width, height in atlas pixels.
+    pub rect   : (u32, u32, u32, u32),
+}
+
+/// Rasterized glyphs packed into shelves of a single texture.
+/// Glyphs are added on first use, the texture doubles in height when it runs out of space.
+pub(super) struct GlyphAtlas {
+    pub texture : Texture,
+
+    image   : RgbaImage,
+    glyphs  : HashMap<GlyphKey, Option<AtlasGlyph>>,
+    max     : u32,
+
+    shelf_x      : u32,
+    shelf_y      : u32,
+    shelf_height : u32,
+
+    /// Rows written since the last upload.
+    dirty   : Option<(u32, u32)>,
+    resized : bool,
+}
+
+impl GlyphAtlas {
+    pub fn new(graphics: &GraphicsContext) -> Self {
+        let image = RgbaImage::from_pixel(WIDTH, INITIAL_HEIGHT, Rgba([255, 255, 255, 0]));
+        let texture = Self::make_texture(graphics, &image);
+
+        return Self {
+            texture,
+
+            image,
+            glyphs  : HashMap::new(),
+            max     : graphics.device.limits().max_texture_dimension_2d,
+
+            shelf_x      : 0,
+            shelf_y      : 0,
+            shelf_height : 0,
+
+            dirty   : None,
+            resized : false,
+        };
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        return self.image.dimensions();
+    }
+
+    /// Returns `None` for glyphs without a bitmap, like spaces.
+    pub fn glyph(&mut self, fonts: &[Font], key: GlyphKey) -> Option<AtlasGlyph> {
+        if let Some(glyph) = self.glyphs.get(&key) {
+            return *glyph;
+        }
+
+        let (metrics, coverage) = fonts[key.font].font.rasterize_indexed(key.index, f32::from_bits(key.px));
+        let glyph = if metrics.width == 0 || metrics.height == 0 {
+            None
+        } else {
+            self.insert(metrics.width as u32, metrics.height as u32, &coverage).map(|(x, y)| AtlasGlyph {
+                offset : (metrics.xmin as f32, -(metrics.ymin as f32 + metrics.height as f32)),
+                rect   : (x, y, metrics.width as u32, metrics.height as u32),
+            })
+        };
+
+        self.glyphs.insert(key, glyph);
+        return glyph;
+    }
+
+    fn insert(&mut self, width: u32, height: u32, coverage: &[u8]) -> Option<(u32, u32)> {
+        let (x, y) = self.allocate(width + PADDING * 2, height + PADDING * 2)?;
+        let (x, y) = (x + PADDING, y + PADDING);
+
+        for row in 0 .. height {
+            for column in 0 .. width {
+                let alpha = coverage[(row * width + column) as usize];
+                self.image.put_pixel(x + column, y + row, Rgba([255, 255, 255, alpha]));
+            }
+        }
+
+        let (start, end) = self.dirty.unwrap_or((y, y + height));
+        self.dirty = Some((start.min(y), end.max(y + height)));
+
+        return Some((x, y));
+    }
+
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        if width > WIDTH {
+            warn!("Glyph of {}px is wider than the glyph atlas", width);
+            return None;
+        }
+
+        // Next shelf
+        if self.shelf_x + width > WIDTH {
+            self.shelf_y += self.shelf_height;
+            self.shelf_x = 0;
+            self.shelf_height = 0;
+        }
+
+        while self.shelf_y + height > self.image.height() {
+            if !self.grow() {
+                warn!("Glyph atlas is full at {}x{}", WIDTH, self.image.height());
+                return None;
+            }
+        }
+
+        let position = (self.shelf_x, self.shelf_y);
+        self.shelf_x += width;
+        self.shelf_height = self.shelf_height.max(height);
+
+        return Some(position);
+    }
+
+    fn grow(&mut self) -> bool {
+        let height = self.image.height() * 2;
+        if height > self.max {
+            return false;
+        }
+
+        let mut image = RgbaImage::from_pixel(WIDTH, height, Rgba([255, 255, 255, 0]));
+        image::imageops::replace(&mut image, &self.image, 0, 0);
+        self.image = image;
+        self.resized = true;
+
+        return true;
+    }
+
+    /// Sends new glyphs to the GPU, growing the texture along with the atlas.
+    pub fn upload(&mut self, graphics: &GraphicsContext) {
+        if self.resized {
+            self.texture.resize(graphics, WIDTH, self.image.height());
+            self.resized = false;
+        }
+
+        if let Some((start, end)) = self.dirty.take() {
+            let rows = &self.image.as_raw()[(4 * WIDTH * start) as usize .. (4 * WIDTH * end) as usize];
+            self.texture.write_region(graphics, 0, start, WIDTH, end - start, rows).unwrap();
+            self.texture.update_mipmaps(graphics);
+        }
+    }
+
+    fn make_texture(graphics: &GraphicsContext, image: &RgbaImage) -> Texture {
+        let image = DynamicImage::ImageRgba8(image.clone());
+        return Texture::from_image(graphics, &image, wgpu::FilterMode::Linear, Some("glyph_atlas")).unwrap();
+    }
+}
\ No newline at end of file
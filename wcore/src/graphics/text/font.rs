@@ -0,0 +1,25 @@
+use std::{path::Path, fs};
+
+use color_eyre::{Result, Report};
+
+/// A TrueType or OpenType font, parsed once and shared by every size it is drawn at.
+#[derive(Clone)]
+pub struct Font {
+    pub(super) font: fontdue::Font,
+}
+
+impl Font {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        return Self::from_bytes(&bytes);
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let font = fontdue::Font::from_bytes(bytes, fontdue::FontSettings::default()).map_err(Report::msg)?;
+        return Ok(Self { font });
+    }
+
+    pub fn has_glyph(&self, character: char) -> bool {
+        return self.font.lookup_glyph_index(character) != 0;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,208 @@
+use std::ops::Range;
+
+use super::{font::Font, Align};
+
+/// A glyph with its pen position on the baseline, in pixels relative to the top left of the text.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct PositionedGlyph {
+    pub font  : usize,
+    pub index : u16,
+    pub x     : f32,
+    pub y     : f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct TextLayout {
+    pub glyphs : Vec<PositionedGlyph>,
+    pub width  : f32,
+    pub height : f32,
+}
+
+struct Shaped {
+    character : char,
+    font      : usize,
+    index     : u16,
+    advance   : f32,
+    /// Kerning against the previous glyph, dropped at the start of a line.
+    kern      : f32,
+}
+
+/// Lays out text at `px` pixels, wrapping lines longer than `max_width`.
+/// Characters missing from the first font are looked up in the following ones.
+pub(super) fn layout(fonts: &[Font], text: &str, px: f32, line_height: f32, max_width: Option<f32>, align: Align) -> TextLayout {
+    let metrics = fonts[0].font.horizontal_line_metrics(px);
+    let ascent = metrics.map_or(px, |metrics| metrics.ascent);
+    let advance = metrics.map_or(px, |metrics| metrics.new_line_size) * line_height;
+
+    let mut layout = TextLayout::default();
+    let mut baseline = ascent;
+    let mut lines = 0;
+
+    for paragraph in text.split('\n') {
+        let shaped = shape(fonts, paragraph.trim_end_matches('\r'), px);
+
+        for line in wrap(&shaped, max_width) {
+            let line = &shaped[line];
+            let width = width(line);
+            let mut x = match align {
+                Align::Left   => 0.0,
+                Align::Center => (max_width.unwrap_or(0.0) - width) / 2.0,
+                Align::Right  => max_width.unwrap_or(0.0) - width,
+            };
+
+            for (i, glyph) in line.iter().enumerate() {
+                if i > 0 {
+                    x += glyph.kern;
+                }
+
+                layout.glyphs.push(PositionedGlyph { font: glyph.font, index: glyph.index, x, y: baseline });
+                x += glyph.advance;
+            }
+
+            layout.width = layout.width.max(width);
+            baseline += advance;
+            lines += 1;
+        }
+    }
+
+    layout.height = lines as f32 * advance;
+    return layout;
+}
+
+fn shape(fonts: &[Font], text: &str, px: f32) -> Vec<Shaped> {
+    let mut shaped: Vec<Shaped> = Vec::with_capacity(text.len());
+
+    for character in text.chars() {
+        let font = fonts.iter().position(|font| font.has_glyph(character)).unwrap_or(0);
+        let index = fonts[font].font.lookup_glyph_index(character);
+        let advance = fonts[font].font.metrics_indexed(index, px).advance_width;
+
+        // Kerning pairs only exist within a font
+        let kern = match shaped.last() {
+            Some(previous) if previous.font == font => fonts[font].font.horizontal_kern_indexed(previous.index, index, px).unwrap_or(0.0),
+            _                                       => 0.0,
+        };
+
+        shaped.push(Shaped { character, font, index, advance, kern });
+    }
+
+    return shaped;
+}
+
+/// Greedy line breaking, falls back to breaking inside a word if it does not fit on its own line.
+fn wrap(shaped: &[Shaped], max_width: Option<f32>) -> Vec<Range<usize>> {
+    let max_width = match max_width {
+        Some(max_width) => max_width,
+        None            => return vec![Range { start: 0, end: shaped.len() }],
+    };
+
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut opportunity = None;
+
+    for i in 0 .. shaped.len() {
+        if i > start && can_break(shaped[i - 1].character, shaped[i].character) {
+            opportunity = Some(i);
+        }
+
+        // Trailing whitespace may hang past the edge, the rest of a word moved to the next line may not fit either
+        while i > start && !shaped[i].character.is_whitespace() && width(&shaped[start ..= i]) > max_width {
+            let end = opportunity.unwrap_or(i);
+            lines.push(start .. end);
+            start = end;
+            opportunity = None;
+        }
+    }
+
+    lines.push(start .. shaped.len());
+    return lines;
+}
+
+/// Width without kerning into the first glyph and without trailing whitespace.
+fn width(line: &[Shaped]) -> f32 {
+    let end = line.iter().rposition(|glyph| !glyph.character.is_whitespace()).map_or(0, |i| i + 1);
+    return line[.. end].iter().enumerate().map(|(i, glyph)| if i > 0 { glyph.kern + glyph.advance } else { glyph.advance }).sum();
+}
+
+fn can_break(previous: char, next: char) -> bool {
+    if is_closing(next) || is_opening(previous) {
+        return false;
+    }
+
+    return (previous.is_whitespace() && !next.is_whitespace())
+        || previous == '-'
+        || is_cjk(previous)
+        || is_cjk(next);
+}
+
+/// Ideographic scripts break between any two characters.
+fn is_cjk(character: char) -> bool {
+    return matches!(character as u32,
+        0x2E80 ..= 0x9FFF   // Radicals, punctuation, kana, unified ideographs
+      | 0xAC00 ..= 0xD7AF   // Hangul syllables
+      | 0xF900 ..= 0xFAFF   // Compatibility ideographs
+      | 0xFF00 ..= 0xFFEF   // Fullwidth forms
+      | 0x20000 ..= 0x3FFFF // Supplementary ideographs
+    );
+}
+
+/// Punctuation that must not start a line.
+fn is_closing(character: char) -> bool {
+    return matches!(character, '.' | ',' | ':' | ';' | '!' | '?' | ')' | ']' | '}'
+        | '、' | '。' | '，' | '．' | '！' | '？' | '：' | '；' | '）' | '」' | '』' | '】' | '〕' | '〉' | '》' | 'ー' | '々');
+}
+
+/// Punctuation that must not end a line.
+fn is_opening(character: char) -> bool {
+    return matches!(character, '(' | '[' | '{' | '（' | '「' | '『' | '【' | '〔' | '〈' | '《');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `W` is three units wide, everything else one.
+    fn shaped(text: &str) -> Vec<Shaped> {
+        return text.chars().map(|character| Shaped {
+            character,
+            font    : 0,
+            index   : 0,
+            advance : if character == 'W' { 3.0 } else { 1.0 },
+            kern    : 0.0,
+        }).collect();
+    }
+
+    fn lines(text: &str, max_width: f32) -> Vec<String> {
+        let chars = text.chars().collect::<Vec<_>>();
+        return wrap(&shaped(text), Some(max_width)).into_iter().map(|line| chars[line].iter().collect()).collect();
+    }
+
+    #[test]
+    fn unlimited_width_is_one_line() {
+        assert_eq!(wrap(&shaped("hello world"), None), vec![0 .. 11]);
+    }
+
+    #[test]
+    fn wraps_at_spaces() {
+        assert_eq!(lines("the quick brown fox", 10.0), ["the quick ", "brown fox"]);
+        assert_eq!(lines("the quick brown fox", 5.0), ["the ", "quick ", "brown ", "fox"]);
+    }
+
+    #[test]
+    fn wraps_between_ideographs() {
+        assert_eq!(lines("日本語のテキスト", 3.0), ["日本語", "のテキ", "スト"]);
+        // Closing punctuation stays on the line before it
+        assert_eq!(lines("日本語。です", 3.0), ["日本", "語。で", "す"]);
+    }
+
+    #[test]
+    fn breaks_words_longer_than_a_line() {
+        assert_eq!(lines("abcdefghij", 3.0), ["abc", "def", "ghi", "j"]);
+        assert_eq!(lines("a abcdefghij b", 4.0), ["a ", "abcd", "efgh", "ij b"]);
+    }
+
+    #[test]
+    fn rest_of_a_moved_word_is_broken_again() {
+        assert_eq!(lines("a WWx", 5.0), ["a ", "W", "Wx"]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,157 @@
+pub mod font;
+mod atlas;
+mod layout;
+
+use cgmath::{vec4, Vector4};
+
+use self::{font::Font, atlas::{GlyphAtlas, GlyphKey}};
+
+use super::{
+    context::GraphicsContext, scene::Scene, bindable::Bindable, drawable::Drawable,
+    pipeline::{text::TextPipeline, Pipeline, shader::scene::SceneSlot},
+    primitive::mesh::{instanced::InstancedMesh, data::{glyph::Glyph, vertex::Vertex}},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TextStyle {
+    /// Font size in logical pixels.
+    pub size        : f32,
+    pub color       : Vector4<f32>,
+    /// Lines are aligned within `max_width`, or around the text position if there is none.
+    pub align       : Align,
+    /// Wrap lines longer than this.
+    pub max_width   : Option<f32>,
+    /// Multiplier for the font's line spacing.
+    pub line_height : f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        return Self {
+            size        : 16.0,
+            color       : vec4(1.0, 1.0, 1.0, 1.0),
+            align       : Align::Left,
+            max_width   : None,
+            line_height : 1.0,
+        };
+    }
+}
+
+/// Draws text queued during a frame in a single instanced draw call.
+/// Glyphs are rasterized at the physical pixel size on first use and cached in an atlas.
+pub struct TextRenderer {
+    fonts        : Vec<Font>,
+    scale_factor : f64,
+
+    atlas    : GlyphAtlas,
+    pipeline : TextPipeline,
+    mesh     : InstancedMesh<Glyph, Vertex>,
+
+    /// UVs are kept in atlas pixels until `prepare`, the atlas may still grow.
+    queued   : Vec<Glyph>,
+}
+
+impl TextRenderer {
+    /// `fonts` is the fallback chain, characters missing from a font are taken from the next one.
+    pub fn new(graphics: &GraphicsContext, scene: &impl Scene, fonts: Vec<Font>) -> Self {
+        assert!(!fonts.is_empty(), "TextRenderer needs at least one font");
+
+        let pipeline = TextPipeline::new(graphics, scene, false);
+        let (vertices, indices) = Vertex::indexed_rect(0.0, 1.0);
+        let mesh = InstancedMesh::indexed(&graphics.device, vertices, indices, vec![]);
+
+        return Self {
+            fonts,
+            scale_factor: graphics.scale_factor,
+
+            atlas: GlyphAtlas::new(graphics),
+            pipeline,
+            mesh,
+
+            queued: vec![],
+        };
+    }
+
+    pub fn fonts(&self) -> &[Font] {
+        return &self.fonts;
+    }
+
+    pub fn push_font(&mut self, font: Font) {
+        self.fonts.push(font);
+    }
+
+    pub fn rescale(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    /// Size of the laid out text in logical pixels.
+    pub fn measure(&self, text: &str, style: &TextStyle) -> (f32, f32) {
+        let scale = self.scale_factor as f32;
+        let layout = layout::layout(&self.fonts, text, style.size * scale, style.line_height, style.max_width.map(|width| width * scale), style.align);
+
+        return (layout.width / scale, layout.height / scale);
+    }
+
+    /// Queues text with its top left corner at `x`, `y`, it is drawn after the next `prepare`.
+    pub fn queue(&mut self, text: &str, x: f32, y: f32, style: &TextStyle) {
+        let scale = self.scale_factor as f32;
+        let px = style.size * scale;
+        let layout = layout::layout(&self.fonts, text, px, style.line_height, style.max_width.map(|width| width * scale), style.align);
+
+        // Snapped to physical pixels, the atlas is sampled texel for texel
+        let origin = ((x * scale).round(), (y * scale).round());
+
+        for glyph in layout.glyphs {
+            let key = GlyphKey { font: glyph.font, index: glyph.index, px: px.to_bits() };
+            let Some(cached) = self.atlas.glyph(&self.fonts, key) else {
+                continue;
+            };
+
+            let (u, v, width, height) = cached.rect;
+            let left = origin.0 + (glyph.x + cached.offset.0).round();
+            let top = origin.1 + (glyph.y + cached.offset.1).round();
+
+            self.queued.push(Glyph {
+                rect  : vec4(left / scale, top / scale, width as f32 / scale, height as f32 / scale),
+                uv    : vec4(u as f32, v as f32, (u + width) as f32, (v + height) as f32),
+                color : style.color,
+            });
+        }
+    }
+
+    /// Uploads new glyphs and the queued text, clearing the queue for the next frame.
+    pub fn prepare(&mut self, graphics: &GraphicsContext) {
+        self.atlas.upload(graphics);
+
+        let (width, height) = self.atlas.size();
+        let size = vec4(width as f32, height as f32, width as f32, height as f32);
+
+        self.mesh.instances = self.queued.drain(..).map(|glyph| Glyph { uv: glyph.uv.zip(size, |uv, size| uv / size), .. glyph }).collect();
+        self.mesh.upload_instances(&graphics.device, &graphics.queue);
+    }
+}
+
+impl Drawable for TextRenderer {
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.mesh.instances.is_empty() {
+            return;
+        }
+
+        self.pipeline.attach(render_pass);
+        self.atlas.texture.bind(render_pass, 1);
+        self.mesh.draw(render_pass);
+    }
+}
+
+unsafe impl SceneSlot for TextRenderer {
+    fn update(&self, queue: &wgpu::Queue, scene: &impl Scene) {
+        self.pipeline.update(queue, scene);
+    }
+}
\ No newline at end of file
@@ -1,148 +1,340 @@
-use std::{path::Path, fs};
+use std::{path::Path, fs, sync::{Arc, atomic::{AtomicBool, Ordering}}};
 
 use image::{GenericImageView, DynamicImage};
-use wgpu::{Device, Queue, FilterMode, BindGroupLayout};
-use color_eyre::Result;
+use wgpu::{FilterMode, BindGroupLayout, util::DeviceExt};
+use color_eyre::{Result, eyre::bail};
 
-use super::bindable::Bindable;
+use super::{bindable::Bindable, context::GraphicsContext, sampler::SamplerSettings, mipmap::MipmapGenerator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TextureSettings {
+    pub sampler : SamplerSettings,
+    /// Generates the full mip chain on the GPU, for textures drawn smaller than their size.
+    pub mipmaps : bool,
+}
+
+impl From<FilterMode> for TextureSettings {
+    fn from(filter_mode: FilterMode) -> Self {
+        return Self { sampler: filter_mode.into(), mipmaps: false };
+    }
+}
+
+impl From<SamplerSettings> for TextureSettings {
+    fn from(sampler: SamplerSettings) -> Self {
+        return Self { sampler, mipmaps: false };
+    }
+}
 
 pub struct Texture {
-    pub sampler : wgpu::Sampler,
+    pub sampler : Arc<wgpu::Sampler>,
     pub texture : wgpu::Texture,
     pub view    : wgpu::TextureView,
+
+    size          : (u32, u32),
+    format        : wgpu::TextureFormat,
+    settings      : TextureSettings,
+    label         : Option<String>,
+    params        : wgpu::Buffer,
+    /// Set by writes, cleared by `update_mipmaps`.
+    mipmaps_dirty : AtomicBool,
     
     bind_group        : wgpu::BindGroup,
     bind_group_layout : wgpu::BindGroupLayout,
 }
 
 impl Texture {
-    pub fn from_path(device      : &Device,
-                     queue       : &Queue,
-                     path        : impl AsRef<Path>,
-                     filter_mode : FilterMode,
-                     label       : &str) -> Result<Self> {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    pub const LAYOUT_ENTRIES: &[wgpu::BindGroupLayoutEntry] = &[
+        wgpu::BindGroupLayoutEntry {
+            binding    : 0,
+            visibility : wgpu::ShaderStages::FRAGMENT,
+            ty         : wgpu::BindingType::Texture {
+                multisampled   : false,
+                view_dimension : wgpu::TextureViewDimension::D2,
+                sample_type    : wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count      : None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding    : 1,
+            visibility : wgpu::ShaderStages::FRAGMENT,
+            // This should match the filterable field of the corresponding Texture entry above.
+            ty         : wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count      : None,
+        },
+        wgpu::BindGroupLayoutEntry {
+            binding    : 2,
+            visibility : wgpu::ShaderStages::FRAGMENT,
+            ty         : wgpu::BindingType::Buffer {
+                ty                 : wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset : false,
+                min_binding_size   : None,
+            },
+            count      : None,
+        },
+    ];
+
+    pub fn from_path(graphics : &GraphicsContext,
+                     path     : impl AsRef<Path>,
+                     settings : impl Into<TextureSettings>,
+                     label    : &str) -> Result<Self> {
         let bytes = fs::read(path)?;
 
-        return Self::from_bytes(device, queue, &bytes, filter_mode, label);
+        return Self::from_bytes(graphics, &bytes, settings, label);
     }
 
-    pub fn from_bytes(device      : &Device,
-                      queue       : &Queue,
-                      bytes       : &[u8],
-                      filter_mode : FilterMode,
-                      label       : &str) -> Result<Self> {
+    pub fn from_bytes(graphics : &GraphicsContext,
+                      bytes    : &[u8],
+                      settings : impl Into<TextureSettings>,
+                      label    : &str) -> Result<Self> {
         let img = image::load_from_memory(bytes)?;
-        return Self::from_image(device, queue, &img, filter_mode, Some(label));
-    }
-
-    pub fn from_image(device      : &Device,
-                      queue       : &Queue,
-                      img         : &DynamicImage,
-                      filter_mode : FilterMode,
-                      label       : Option<&str>) -> Result<Self> {
-        let rgba = img.to_rgba8();
-        let dimensions = img.dimensions();
-        let size = wgpu::Extent3d {
-            width                 : dimensions.0,
-            height                : dimensions.1,
-            depth_or_array_layers : 1,
+        return Self::from_image(graphics, &img, settings, Some(label));
+    }
+
+    pub fn from_image(graphics : &GraphicsContext,
+                      img      : &DynamicImage,
+                      settings : impl Into<TextureSettings>,
+                      label    : Option<&str>) -> Result<Self> {
+        let (width, height) = img.dimensions();
+        let texture = Self::new(graphics, width, height, settings, label);
+        texture.write_image(graphics, 0, 0, img)?;
+        texture.update_mipmaps(graphics);
+
+        return Ok(texture);
+    }
+
+    /// Creates a transparent texture, filled later with `write` or `write_region`.
+    pub fn new(graphics : &GraphicsContext,
+               width    : u32,
+               height   : u32,
+               settings : impl Into<TextureSettings>,
+               label    : Option<&str>) -> Self {
+        return Self::with_format(graphics, width, height, Self::FORMAT, settings, label);
+    }
+
+    /// Like `new`, for textures that are rendered into with pipelines targeting another format.
+    /// Only `FORMAT` textures can be written to and have mipmaps.
+    pub fn with_format(graphics : &GraphicsContext,
+                       width    : u32,
+                       height   : u32,
+                       format   : wgpu::TextureFormat,
+                       settings : impl Into<TextureSettings>,
+                       label    : Option<&str>) -> Self {
+        let device = &graphics.device;
+        let mut settings = settings.into();
+        settings.mipmaps &= format == Self::FORMAT;
+        let size = (width.max(1), height.max(1));
+
+        let texture = Self::make_texture(device, size, format, &settings, label);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = graphics.samplers.get(device, &settings.sampler);
+
+        // x is the LOD bias, the rest is padding
+        let params = device.create_buffer_init(
+            &wgpu::util::BufferInitDescriptor {
+                label    : Some("texture_params"),
+                contents : bytemuck::cast_slice(&[settings.sampler.lod_bias, 0.0, 0.0, 0.0]),
+                usage    : wgpu::BufferUsages::UNIFORM,
+            }
+        );
+
+        // Binding stuff
+        let bind_group_layout = Self::default_layout(device);
+        let bind_group = Self::make_bind_group(device, &bind_group_layout, &view, &sampler, &params);
+        
+        return Self {
+            texture,
+            view,
+            sampler,
+
+            size,
+            format,
+            settings,
+            label: label.map(String::from),
+            params,
+            mipmaps_dirty: AtomicBool::new(false),
+
+            bind_group,
+            bind_group_layout,
         };
+    }
+
+    fn make_texture(device: &wgpu::Device, size: (u32, u32), format: wgpu::TextureFormat, settings: &TextureSettings, label: Option<&str>) -> wgpu::Texture {
+        let mip_level_count = if settings.mipmaps { MipmapGenerator::mip_level_count(size.0, size.1) } else { 1 };
 
-        let texture = device.create_texture(
+        return device.create_texture(
             &wgpu::TextureDescriptor {
                 label,
-                size,
-                mip_level_count : 1,
+                size            : wgpu::Extent3d {
+                    width                 : size.0,
+                    height                : size.1,
+                    depth_or_array_layers : 1,
+                },
+                mip_level_count,
                 sample_count    : 1,
                 dimension       : wgpu::TextureDimension::D2,
-                format          : wgpu::TextureFormat::Rgba8UnormSrgb,
+                format,
                 usage           : wgpu::TextureUsages::TEXTURE_BINDING
-                                | wgpu::TextureUsages::COPY_DST,
-            }
-        );
-
-        // Upload texture
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                aspect    : wgpu::TextureAspect::All,
-                texture   : &texture,
-                mip_level : 0,
-                origin    : wgpu::Origin3d::ZERO,
-            },
-            &rgba,
-            wgpu::ImageDataLayout {
-                offset         : 0,
-                bytes_per_row  : std::num::NonZeroU32::new(4 * dimensions.0),
-                rows_per_image : std::num::NonZeroU32::new(dimensions.1),
-            },
-            size,
-        );
-
-        // Shaders stuff
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(
-            &wgpu::SamplerDescriptor {
-                address_mode_u : wgpu::AddressMode::ClampToEdge,
-                address_mode_v : wgpu::AddressMode::ClampToEdge,
-                address_mode_w : wgpu::AddressMode::ClampToEdge,
-                mag_filter     : filter_mode,
-                min_filter     : filter_mode,
-                mipmap_filter  : wgpu::FilterMode::Nearest,
-                .. Default::default()
+                                | wgpu::TextureUsages::RENDER_ATTACHMENT
+                                | wgpu::TextureUsages::COPY_DST
+                                | wgpu::TextureUsages::COPY_SRC,
             }
         );
+    }
 
-        // Binding stuff
-        let bind_group_layout = Self::default_layout(device);
-        let bind_group = device.create_bind_group(
+    fn make_bind_group(device: &wgpu::Device, layout: &BindGroupLayout, view: &wgpu::TextureView, sampler: &wgpu::Sampler, params: &wgpu::Buffer) -> wgpu::BindGroup {
+        return device.create_bind_group(
             &wgpu::BindGroupDescriptor {
-                layout: &bind_group_layout,
+                layout,
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding  : 0,
-                        resource : wgpu::BindingResource::TextureView(&view),
+                        resource : wgpu::BindingResource::TextureView(view),
                     },
                     wgpu::BindGroupEntry {
                         binding  : 1,
-                        resource : wgpu::BindingResource::Sampler(&sampler),
-                    }
+                        resource : wgpu::BindingResource::Sampler(sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding  : 2,
+                        resource : params.as_entire_binding(),
+                    },
                 ],
                 label: Some("diffuse_bind_group"),
             }
         );
-        
-        return Ok(Self { texture, view, sampler, bind_group, bind_group_layout });
     }
 
-    pub fn default_layout(device: &wgpu::Device) -> BindGroupLayout {
-        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding    : 0,
-                    visibility : wgpu::ShaderStages::FRAGMENT,
-                    ty         : wgpu::BindingType::Texture {
-                        multisampled   : false,
-                        view_dimension : wgpu::TextureViewDimension::D2,
-                        sample_type    : wgpu::TextureSampleType::Float { filterable: true },
-                    },
-                    count      : None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding    : 1,
-                    visibility : wgpu::ShaderStages::FRAGMENT,
-                    // This should match the filterable field of the corresponding Texture entry above.
-                    ty         : wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count      : None,
+    /// Overwrites the whole texture with tightly packed RGBA8 rows, regenerating mipmaps if the texture has them.
+    pub fn write(&self, graphics: &GraphicsContext, data: &[u8]) -> Result<()> {
+        self.write_region(graphics, 0, 0, self.size.0, self.size.1, data)?;
+        self.update_mipmaps(graphics);
+
+        return Ok(());
+    }
+
+    /// Overwrites a rectangle with tightly packed RGBA8 rows.
+    /// Mipmaps are left outdated, call `update_mipmaps` once after a batch of writes.
+    pub fn write_region(&self, graphics: &GraphicsContext, x: u32, y: u32, width: u32, height: u32, data: &[u8]) -> Result<()> {
+        // Caller input, an overflowing sum must not wrap past the check
+        let right = x.checked_add(width).filter(|&right| right <= self.size.0);
+        let bottom = y.checked_add(height).filter(|&bottom| bottom <= self.size.1);
+        if right.is_none() || bottom.is_none() {
+            bail!("Region {}x{} at ({}, {}) is outside of the {}x{} texture", width, height, x, y, self.size.0, self.size.1);
+        }
+
+        if self.format != Self::FORMAT {
+            bail!("Only {:?} textures can be written to, this one is {:?}", Self::FORMAT, self.format);
+        }
+
+        let bytes_per_row = width.checked_mul(4);
+        let expected = (width as usize).checked_mul(height as usize).and_then(|pixels| pixels.checked_mul(4));
+        let (Some(bytes_per_row), Some(expected)) = (bytes_per_row, expected) else {
+            bail!("Region {}x{} is too large to write", width, height);
+        };
+
+        if data.len() != expected {
+            bail!("Expected {} bytes of RGBA data for {}x{}, got {}", expected, width, height, data.len());
+        }
+
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        graphics.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                aspect    : wgpu::TextureAspect::All,
+                texture   : &self.texture,
+                mip_level : 0,
+                origin    : wgpu::Origin3d { x, y, z: 0 },
+            },
+            data,
+            wgpu::ImageDataLayout {
+                offset         : 0,
+                bytes_per_row  : std::num::NonZeroU32::new(bytes_per_row),
+                rows_per_image : std::num::NonZeroU32::new(height),
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers : 1,
+            },
+        );
+
+        self.mipmaps_dirty.store(self.settings.mipmaps, Ordering::Relaxed);
+        return Ok(());
+    }
+
+    /// Regenerates the mip chain from the base level if it was written to since the last time.
+    pub fn update_mipmaps(&self, graphics: &GraphicsContext) {
+        if !self.mipmaps_dirty.swap(false, Ordering::Relaxed) {
+            return;
+        }
+
+        let mip_level_count = MipmapGenerator::mip_level_count(self.size.0, self.size.1);
+        graphics.mipmap_generator().generate(&graphics.device, &graphics.queue, &self.texture, mip_level_count);
+    }
+
+    /// Overwrites the rectangle covered by the image, with its top left corner at `x`, `y`, like `write_region`.
+    pub fn write_image(&self, graphics: &GraphicsContext, x: u32, y: u32, img: &DynamicImage) -> Result<()> {
+        let (width, height) = img.dimensions();
+        return self.write_region(graphics, x, y, width, height, &img.to_rgba8());
+    }
+
+    /// Recreates the texture and its bind group at a new size, keeping the overlapping content.
+    pub fn resize(&mut self, graphics: &GraphicsContext, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size == self.size {
+            return;
+        }
+
+        let device = &graphics.device;
+        let texture = Self::make_texture(device, size, self.format, &self.settings, self.label.as_deref());
+
+        super::utils::submit(&graphics.queue, device, |encoder| {
+            encoder.copy_texture_to_texture(
+                self.texture.as_image_copy(),
+                texture.as_image_copy(),
+                wgpu::Extent3d {
+                    width                 : size.0.min(self.size.0),
+                    height                : size.1.min(self.size.1),
+                    depth_or_array_layers : 1,
                 },
-            ],
-            label: Some("texture_bind_group_layout"),
+            );
         });
 
-        return bind_group_layout;
+        self.texture = texture;
+        self.view = self.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.bind_group = Self::make_bind_group(device, &self.bind_group_layout, &self.view, &self.sampler, &self.params);
+        self.size = size;
+
+        // The copy only covers the base level
+        self.mipmaps_dirty.store(self.settings.mipmaps, Ordering::Relaxed);
+        self.update_mipmaps(graphics);
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        return self.size;
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        return self.format;
+    }
+
+    pub fn settings(&self) -> &TextureSettings {
+        return &self.settings;
+    }
+
+    pub fn default_layout(device: &wgpu::Device) -> BindGroupLayout {
+        return device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: Self::LAYOUT_ENTRIES,
+            label: Some("texture_bind_group_layout"),
+        });
     }
 }
 
-impl Bindable<[[f32; 4]; 4]> for Texture {
+impl Bindable for Texture {
     fn bind<'a, 'b: 'a>(&'b self, render_pass: &mut wgpu::RenderPass<'a>, index: u32) {
         render_pass.set_bind_group(index, &self.bind_group, &[]);
     }
@@ -151,13 +343,11 @@ impl Bindable<[[f32; 4]; 4]> for Texture {
         return &self.bind_group_layout;
     }
 
-    fn group(&self) -> &wgpu::BindGroup {
-        return &self.bind_group;
+    fn layout_entries(&self) -> &'static [wgpu::BindGroupLayoutEntry] {
+        return Self::LAYOUT_ENTRIES;
     }
 
-    #[allow(unused_variables)] // TODO: remove this annotation
-     fn update(&self, queue: &wgpu::Queue, value: &[[f32; 4]; 4]) {
-        todo!() // Upload new texture data here, I guess
+    fn group(&self) -> &wgpu::BindGroup {
+        return &self.bind_group;
     }
-
 }
\ No newline at end of file
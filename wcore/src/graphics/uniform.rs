@@ -13,6 +13,19 @@ pub struct Uniform<T: Default + Clone + Pod + Zeroable = [[f32; 4]; 4]> {
 }
 
 impl<T: Default + Clone + Pod + Zeroable> Uniform<T> {
+    pub const LAYOUT_ENTRIES: &[wgpu::BindGroupLayoutEntry] = &[
+        wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
+    ];
+
     pub fn new(device: &wgpu::Device) -> Self {
         let buffer = device.create_buffer_init(
             &wgpu::util::BufferInitDescriptor {
@@ -24,18 +37,7 @@ impl<T: Default + Clone + Pod + Zeroable> Uniform<T> {
 
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: None,
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }
-            ],
+            entries: Self::LAYOUT_ENTRIES,
         });
 
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -56,9 +58,15 @@ impl<T: Default + Clone + Pod + Zeroable> Uniform<T> {
             _0: Default::default()
         };
     }
+
+    /// Loads uniform with a new value.
+    /// The value is updated at the end of the RenderPass.
+    pub fn update(&self, queue: &wgpu::Queue, value: &T) {
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(value));
+    }
 }
 
-impl<T: Default + Clone + Pod + Zeroable> Bindable<T> for Uniform<T> {
+impl<T: Default + Clone + Pod + Zeroable> Bindable for Uniform<T> {
     fn bind<'a, 'b: 'a>(&'b self, render_pass: &mut wgpu::RenderPass<'a>, index: u32) {
         render_pass.set_bind_group(index, &self.bind_group, &[]);
     }
@@ -67,13 +75,11 @@ impl<T: Default + Clone + Pod + Zeroable> Bindable<T> for Uniform<T> {
         return &self.bind_group_layout;
     }
 
+    fn layout_entries(&self) -> &'static [wgpu::BindGroupLayoutEntry] {
+        return Self::LAYOUT_ENTRIES;
+    }
+
     fn group(&self) -> &wgpu::BindGroup {
         return &self.bind_group;
     }
-    
-    /// Loads uniform with a new value.
-    /// The value is updated at the end of the RenderPass.
-    fn update(&self, queue: &wgpu::Queue, value: &T) {
-        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(value));
-    }
 }
\ No newline at end of file
@@ -1,43 +1,57 @@
+use instant::Instant;
 use wgpu::{Device, TextureView};
 
-pub fn render_pass<'a>(encoder: &'a mut wgpu::CommandEncoder, view: &'a wgpu::TextureView, depth_buffer: Option<&'a TextureView>) -> wgpu::RenderPass<'a> {
-    return encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-        label: Some("Render Pass"),
-        color_attachments: &[
-            // [[location(0)]] in the fragment shader
-            Some(wgpu::RenderPassColorAttachment {
-                resolve_target: None,
-                view: view,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(
-                        wgpu::Color {
-                            r: 0.01,
-                            g: 0.01,
-                            b: 0.01,
-                            a: 1.0,
-                        }
-                    ),
-                    store: true,
-                }
-            })
-        ],
-        depth_stencil_attachment: if depth_buffer.is_some() { Some(wgpu::RenderPassDepthStencilAttachment {
-            view: depth_buffer.unwrap(),
-            depth_ops: Some(wgpu::Operations {
-                load: wgpu::LoadOp::Clear(1.0),
-                store: true,
-            }),
-            stencil_ops: None,
-        }) } else { None },
-    });
+use crate::timing;
+
+use super::{context::GraphicsContext, pipeline::Pipeline, pass::RenderPassBuilder};
+
+/// Color attachment of a render pass. Multisampled views are resolved into `resolve_target` when the pass ends.
+/// Plain views convert into one without a resolve target, `GraphicsContext::attachment` adds MSAA to the frame.
+#[derive(Clone, Copy)]
+pub struct ColorTarget<'a> {
+    pub view           : &'a TextureView,
+    pub resolve_target : Option<&'a TextureView>,
+}
+
+impl<'a> From<&'a TextureView> for ColorTarget<'a> {
+    fn from(view: &'a TextureView) -> Self {
+        return Self { view, resolve_target: None };
+    }
+}
+
+/// Background of the frame and of targets rendered with `render`.
+pub const CLEAR_COLOR: wgpu::Color = wgpu::Color { r: 0.01, g: 0.01, b: 0.01, a: 1.0 };
+
+/// Clears the target to `CLEAR_COLOR`, use `RenderPassBuilder` to draw over it instead.
+pub fn render_pass<'a>(encoder: &'a mut wgpu::CommandEncoder, target: impl Into<ColorTarget<'a>>, depth_buffer: Option<&'a TextureView>) -> wgpu::RenderPass<'a> {
+    return RenderPassBuilder::new(target)
+        .label("Render Pass")
+        .clear(CLEAR_COLOR)
+        .depth_if(depth_buffer)
+        .begin(encoder);
 }
 
 pub fn render<'a: 'r, 'r>(encoder      : &'a mut wgpu::CommandEncoder,
-                          view         : &'a wgpu::TextureView,
+                          target       : impl Into<ColorTarget<'a>>,
                           depth_buffer : Option<&'a TextureView>,
                           lambda       : impl FnOnce(wgpu::RenderPass<'r>) -> ()) {
     #[allow(unused_mut)]
-    let mut pass = render_pass(encoder, view, depth_buffer);
+    let mut pass = render_pass(encoder, target, depth_buffer);
+    lambda(pass);
+}
+
+/// Renders into the frame `view` with `pipeline` attached, over what previous screens drew.
+/// Uses the MSAA attachment of the context and its depth buffer, cleared first, if the pipeline uses depth.
+pub fn render_with<'a: 'r, 'r>(graphics : &'a GraphicsContext,
+                               encoder  : &'a mut wgpu::CommandEncoder,
+                               view     : &'a wgpu::TextureView,
+                               pipeline : &'a (impl Pipeline + ?Sized),
+                               lambda   : impl FnOnce(wgpu::RenderPass<'r>)) {
+    let mut pass = RenderPassBuilder::new(graphics.attachment(view))
+        .label("Frame Pass")
+        .depth_if(graphics.depth_for(pipeline))
+        .begin(encoder);
+    pipeline.attach(&mut pass);
     lambda(pass);
 }
 
@@ -46,68 +60,33 @@ pub fn submit(queue: &wgpu::Queue, device: &wgpu::Device, lambda: impl FnOnce(&m
     let mut encoder = device.create_command_encoder(&descriptor);
     lambda(&mut encoder);
 
+    let start = Instant::now();
     queue.submit(std::iter::once(encoder.finish()));
+    timing::record_submit(start.elapsed());
 }
 
-pub fn pipeline(device  : &Device,
-                shader  : &wgpu::ShaderModule,
-                config  : &wgpu::SurfaceConfiguration,
-                groups  : &[&wgpu::BindGroupLayout],
-                buffers : &[wgpu::VertexBufferLayout],
-                depth   : bool) -> wgpu::RenderPipeline {
-    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        label                : Some("Render Pipeline Layout"),
-        push_constant_ranges : &[],
-        bind_group_layouts   : groups,
-    });
-
-    return device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-        label  : Some("Render Pipeline"),
-        layout : Some(&pipeline_layout),
-
-        // Shaders
-        vertex: wgpu::VertexState {
-            module      : &shader,
-            entry_point : "vertex_main",
-            buffers     : buffers,
-        },
-
-        fragment: Some(wgpu::FragmentState {
-            module      : &shader,
-            entry_point : "fragment_main",
-            targets     : &[Some(wgpu::ColorTargetState {
-                format     : config.format,
-                blend      : Some(wgpu::BlendState::ALPHA_BLENDING),
-                write_mask : wgpu::ColorWrites::ALL,
-            })],
-        }),
-
-        // Other
-        primitive: wgpu::PrimitiveState {
-            topology           : wgpu::PrimitiveTopology::TriangleList,
-            strip_index_format : None,
-            front_face         : wgpu::FrontFace::Ccw,
-            cull_mode          : Some(wgpu::Face::Back),
-            polygon_mode       : wgpu::PolygonMode::Fill, // Other modes require certain features
-            unclipped_depth    : false,                   // Requires Features::DEPTH_CLIP_CONTROL
-            conservative       : false,                   // Requires Features::CONSERVATIVE_RASTERIZATION
-        },
-
-        depth_stencil: if depth { Some(wgpu::DepthStencilState {
-            format              : wgpu::TextureFormat::Depth32Float,
-            depth_write_enabled : true,
-            depth_compare       : wgpu::CompareFunction::Less,
-            stencil             : wgpu::StencilState::default(),
-            bias                : wgpu::DepthBiasState::default(),
-        }) } else { None },
-        
-        multiview   : None,
-        multisample : wgpu::MultisampleState {
-            count                     : 1,
-            mask                      : !0,
-            alpha_to_coverage_enabled : false,
+/// Texture that is only rendered into, for multisampled color and depth attachments.
+pub fn attachment(device  : &Device,
+                  size    : (u32, u32),
+                  format  : wgpu::TextureFormat,
+                  samples : u32,
+                  label   : &str) -> (wgpu::Texture, TextureView) {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label           : Some(label),
+        size            : wgpu::Extent3d {
+            width                 : size.0,
+            height                : size.1,
+            depth_or_array_layers : 1,
         },
+        mip_level_count : 1,
+        sample_count    : samples,
+        dimension       : wgpu::TextureDimension::D2,
+        format,
+        usage           : wgpu::TextureUsages::RENDER_ATTACHMENT,
     });
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    return (texture, view);
 }
 
 pub fn bind<'a, 'b: 'a>(bind_group: &'b wgpu::BindGroup, render_pass: &mut wgpu::RenderPass<'a>, index: u32) {
@@ -0,0 +1,48 @@
+use crate::config::Fullscreen;
+
+/// Requests from screens to the application, applied after the current event is handled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Exit,
+    SetTitle(String),
+    SetFullscreen(Fullscreen),
+    ToggleFullscreen,
+}
+
+#[derive(Debug, Default)]
+pub struct AppHandle {
+    commands : Vec<Command>,
+    redraw   : bool,
+}
+
+impl AppHandle {
+    /// Exits without asking screens, use after a close request was confirmed.
+    pub fn exit(&mut self) {
+        self.commands.push(Command::Exit);
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.commands.push(Command::SetTitle(title.into()));
+    }
+
+    pub fn set_fullscreen(&mut self, fullscreen: Fullscreen) {
+        self.commands.push(Command::SetFullscreen(fullscreen));
+    }
+
+    pub fn toggle_fullscreen(&mut self) {
+        self.commands.push(Command::ToggleFullscreen);
+    }
+
+    /// Marks the frame dirty, only needed for screens with [`RedrawPolicy::OnDemand`](crate::graphics::screen::RedrawPolicy::OnDemand).
+    pub fn request_redraw(&mut self) {
+        self.redraw = true;
+    }
+
+    pub(crate) fn take_redraw(&mut self) -> bool {
+        return std::mem::take(&mut self.redraw);
+    }
+
+    pub(crate) fn take(&mut self) -> Vec<Command> {
+        return std::mem::take(&mut self.commands);
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+
+use color_eyre::eyre::{Report, Result};
+use image::RgbaImage;
+use instant::{Duration, Instant};
+use log::{error, info};
+
+use crate::{AppConfig, input::Input, handle::Command, timing::{self, FrameTimings}, graphics::{context::GraphicsContext, screen::Screen, capture}};
+
+/// Drives screens without a window.
+/// Frames are rendered into [`GraphicsContext::offscreen`], time only advances by the given deltas.
+pub struct Headless<State> {
+    pub graphics : GraphicsContext,
+    pub state    : State,
+    pub screens  : Vec<Box<dyn Screen<State>>>,
+
+    config : AppConfig,
+    inputs : VecDeque<Input>,
+    time   : Duration,
+    frame  : u64,
+    title  : String,
+    exited : bool,
+}
+
+impl<State> Headless<State> {
+    pub fn new(config: AppConfig, state_lambda: impl FnOnce(&mut GraphicsContext) -> State, screens_lambda: impl FnOnce(&mut GraphicsContext, &mut Vec<Box<dyn Screen<State>>>)) -> Result<Self> {
+        let mut graphics = pollster::block_on(GraphicsContext::headless(&config))
+            .ok_or_else(|| Report::msg("No suitable adapter found"))?;
+
+        let mut state = state_lambda(&mut graphics);
+        let mut screens = vec![];
+        screens_lambda(&mut graphics, &mut screens);
+
+        let (width, height) = (graphics.surface_configuration.width, graphics.surface_configuration.height);
+        for screen in &mut screens {
+            screen.resize(&mut state, width, height, &mut graphics);
+        }
+
+        return Ok(Self {
+            graphics,
+            state,
+            screens,
+
+            title  : config.title.clone(),
+            config,
+            inputs : VecDeque::new(),
+            time   : Duration::ZERO,
+            frame  : 0,
+            exited : false,
+        });
+    }
+
+    /// Queues input to be delivered at the start of the next frame.
+    pub fn inject(&mut self, input: Input) {
+        self.inputs.push_back(input);
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.graphics.resize(width, height);
+            for screen in &mut self.screens {
+                screen.resize(&mut self.state, width, height, &mut self.graphics);
+            }
+        }
+    }
+
+    /// Emulates a device loss by recreating the graphics context.
+    pub fn recreate(&mut self) -> Result<()> {
+        let (width, height) = (self.graphics.surface_configuration.width, self.graphics.surface_configuration.height);
+        let config = AppConfig { width, height, .. self.config.clone() };
+
+        let mut graphics = pollster::block_on(GraphicsContext::headless(&config))
+            .ok_or_else(|| Report::msg("No suitable adapter found"))?;
+        graphics.scale_factor = self.graphics.scale_factor;
+        self.graphics = graphics;
+
+        for screen in &mut self.screens {
+            screen.recreate(&mut self.state, &mut self.graphics);
+        }
+
+        return Ok(());
+    }
+
+    /// Emulates the window moving to a monitor with a different scale factor.
+    pub fn rescale(&mut self, scale_factor: f64) {
+        self.graphics.scale_factor = scale_factor;
+        for screen in &mut self.screens {
+            screen.rescale(&mut self.state, scale_factor, &mut self.graphics);
+        }
+    }
+
+    /// Delivers queued input, then updates and renders every screen once.
+    pub fn step(&mut self, delta: Duration) {
+        while let Some(input) = self.inputs.pop_front() {
+            for screen in &mut self.screens {
+                screen.input(&mut self.state, &input, &mut self.graphics);
+            }
+        }
+
+        self.graphics.shaders.poll(&self.graphics);
+
+        let update_start = Instant::now();
+        for screen in &mut self.screens {
+            screen.update(&mut self.state, delta, &mut self.graphics);
+        }
+
+        let update = update_start.elapsed();
+        let view = self.graphics.offscreen.as_ref()
+            .expect("Headless context must have an offscreen target")
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let render_start = Instant::now();
+        self.graphics.clear_frame(&view);
+        for screen in &mut self.screens {
+            screen.render(&mut self.state, &view, &mut self.graphics);
+        }
+
+        let render = render_start.elapsed();
+        let submit = timing::take_submit();
+        self.graphics.frame_stats.push(FrameTimings {
+            update,
+            encode  : render.saturating_sub(submit),
+            submit,
+            present : Duration::ZERO,
+            total   : delta,
+        });
+
+        if let Some(path) = self.graphics.take_screenshot() {
+            match self.capture().and_then(|image| capture::save(&image, &path)) {
+                Ok(()) => info!("Saved screenshot to {}", path.display()),
+                Err(e) => error!("Failed to save screenshot: {e}"),
+            }
+        }
+
+        self.graphics.device.poll(wgpu::Maintain::Wait);
+        self.time += delta;
+        self.frame += 1;
+
+        self.apply_commands();
+    }
+
+    /// Emulates the user closing the window, returns whether no screen vetoed it.
+    pub fn close(&mut self) -> bool {
+        let mut close = true;
+        for screen in &mut self.screens {
+            close &= screen.close_requested(&mut self.state, &mut self.graphics);
+        }
+
+        self.apply_commands();
+        if close {
+            self.exit();
+        }
+
+        return close;
+    }
+
+    /// Notifies screens that the application is exiting.
+    pub fn exit(&mut self) {
+        if !self.exited {
+            self.exited = true;
+            for screen in &mut self.screens {
+                screen.exit(&mut self.state, &mut self.graphics);
+            }
+        }
+    }
+
+    fn apply_commands(&mut self) {
+        for command in self.graphics.app.take() {
+            match command {
+                Command::Exit            => self.exit(),
+                Command::SetTitle(title) => self.title = title,
+
+                // There is no window to make fullscreen
+                Command::SetFullscreen(_) | Command::ToggleFullscreen => {}
+            }
+        }
+    }
+
+    /// Steps `frames` frames with a fixed delta.
+    pub fn run(&mut self, frames: u64, delta: Duration) {
+        for _ in 0 .. frames {
+            self.step(delta);
+        }
+    }
+
+    /// Reads back the last rendered frame.
+    pub fn capture(&self) -> Result<RgbaImage> {
+        let texture = self.graphics.offscreen.as_ref()
+            .ok_or_else(|| Report::msg("Headless context has no offscreen target"))?;
+
+        return self.graphics.capture(texture);
+    }
+
+    pub fn time(&self) -> Duration {
+        return self.time;
+    }
+
+    pub fn frame(&self) -> u64 {
+        return self.frame;
+    }
+
+    pub fn title(&self) -> &str {
+        return &self.title;
+    }
+
+    pub fn exited(&self) -> bool {
+        return self.exited;
+    }
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use winit::event::WindowEvent;
+
+pub use winit::event::{ElementState, MouseButton, VirtualKeyCode, ModifiersState};
+
+/// Input forwarded to screens.
+/// Decoupled from winit events so it can be constructed by hand, e.g. in headless mode.
+/// Positions are in logical pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    CursorMoved      { x: f64, y: f64 },
+    MouseInput       { button: MouseButton, state: ElementState },
+    KeyboardInput    { key: Option<VirtualKeyCode>, scancode: u32, state: ElementState },
+    ModifiersChanged (ModifiersState),
+
+    /// A file is dragged over the window, repeated for every file.
+    /// `x` and `y` are the last `CursorMoved` position. No cursor movement is reported during a drag, so it is usually stale.
+    FileHovered        { path: PathBuf, x: f64, y: f64 },
+    /// Same position as `FileHovered`, which is not necessarily where the file was dropped.
+    FileDropped        { path: PathBuf, x: f64, y: f64 },
+    FileHoverCancelled,
+}
+
+impl Input {
+    /// Most platforms don't report the cursor during drag and drop,
+    /// so file events use the last known `cursor` position.
+    pub fn from_window_event(event: &WindowEvent, scale_factor: f64, cursor: (f64, f64)) -> Option<Self> {
+        return match event {
+            WindowEvent::CursorMoved { position, .. } => Some(Input::CursorMoved {
+                x: position.x / scale_factor,
+                y: position.y / scale_factor,
+            }),
+
+            WindowEvent::MouseInput { state, button, .. } => Some(Input::MouseInput {
+                button : *button,
+                state  : *state,
+            }),
+
+            WindowEvent::KeyboardInput { input, .. } => Some(Input::KeyboardInput {
+                key      : input.virtual_keycode,
+                scancode : input.scancode,
+                state    : input.state,
+            }),
+
+            WindowEvent::ModifiersChanged(modifiers) => Some(Input::ModifiersChanged(*modifiers)),
+
+            WindowEvent::HoveredFile(path) => Some(Input::FileHovered {
+                path : path.clone(),
+                x    : cursor.0,
+                y    : cursor.1,
+            }),
+
+            WindowEvent::DroppedFile(path) => Some(Input::FileDropped {
+                path : path.clone(),
+                x    : cursor.0,
+                y    : cursor.1,
+            }),
+
+            WindowEvent::HoveredFileCancelled => Some(Input::FileHoverCancelled),
+
+            _ => None,
+        };
+    }
+
+    pub fn key_pressed(key: VirtualKeyCode) -> Self {
+        return Input::KeyboardInput { key: Some(key), scancode: 0, state: ElementState::Pressed };
+    }
+
+    pub fn key_released(key: VirtualKeyCode) -> Self {
+        return Input::KeyboardInput { key: Some(key), scancode: 0, state: ElementState::Released };
+    }
+}
@@ -1,26 +1,37 @@
 pub mod graphics;
 pub mod audio;
 pub mod clock;
+pub mod input;
+pub mod headless;
+pub mod config;
+pub mod handle;
+pub mod timing;
 
-use graphics::{context::GraphicsContext, screen::Screen};
-use winit::{event_loop::{EventLoop, ControlFlow}, window::WindowBuilder, dpi::{LogicalSize, PhysicalSize}, event::{Event, WindowEvent}};
+pub use config::AppConfig;
 
-pub struct AppConfig {
-    pub width  : u32,
-    pub height : u32,
-    pub title  : String,
-}
+// Lets derived code refer to `::wcore` from inside this crate as well
+extern crate self as wcore;
 
-impl Default for AppConfig {
-    fn default() -> Self {
-        return Self {
-            width  : 1200,
-            height : 800,
-            title  : String::from("App"),
-        };
-    }
+#[doc(hidden)]
+pub mod __derive {
+    pub use wgpu;
+    pub use bytemuck;
+    pub use crate::graphics::primitive::mesh::layout::{VertexLayout, VertexField};
 }
 
+use config::Fullscreen;
+use graphics::{context::GraphicsContext, screen::{Screen, RedrawPolicy}, capture};
+use handle::Command;
+use input::Input;
+use timing::FrameTimings;
+use instant::{Instant, Duration};
+use log::{error, info, warn};
+use color_eyre::eyre::{Report, Result};
+use winit::{event_loop::{EventLoop, ControlFlow}, window::WindowBuilder, dpi::{LogicalSize, PhysicalSize}, event::{Event, WindowEvent}};
+
+/// Consecutive lost surface errors before the device is considered lost.
+const MAX_LOST_FRAMES: u32 = 3;
+
 pub struct App<State> {
     pub screens: Vec<Box<dyn Screen<State>>>
 }
@@ -34,59 +45,194 @@ impl<State> Default for App<State> {
 }
 
 impl<State: 'static> App<State> {
-    pub fn run(mut self, config: AppConfig, state_lambda: impl FnOnce(&mut GraphicsContext) -> State, screens_lambda: impl FnOnce(&mut GraphicsContext, &mut Vec<Box<dyn Screen<State>>>)) {
-        pollster::block_on(async {
+    /// Only returns if the window or graphics context cannot be created.
+    pub fn run(mut self, config: AppConfig, state_lambda: impl FnOnce(&mut GraphicsContext) -> State, screens_lambda: impl FnOnce(&mut GraphicsContext, &mut Vec<Box<dyn Screen<State>>>)) -> Result<()> {
+        return pollster::block_on(async {
             let event_loop = EventLoop::new();
-            let window = WindowBuilder::new()
-                .with_title(config.title)
+            let mut builder = WindowBuilder::new()
+                .with_title(&config.title)
                 .with_inner_size(LogicalSize::new(config.width, config.height))
-                .build(&event_loop)
-                .unwrap();
+                .with_resizable(config.resizable)
+                .with_fullscreen(config.fullscreen.to_winit(event_loop.primary_monitor()));
+
+            if let Some((width, height)) = config.min_size {
+                builder = builder.with_min_inner_size(LogicalSize::new(width, height));
+            }
+
+            let window = builder.build(&event_loop)?;
+            let mut graphics = GraphicsContext::new(&window, &config).await
+                .ok_or_else(|| Report::msg("No suitable graphics adapter found"))?;
 
-            let mut graphics = GraphicsContext::new(&window).await.unwrap();
             let mut state = state_lambda(&mut graphics);
             screens_lambda(&mut graphics, &mut self.screens);
 
-            event_loop.run(move |event, _, control_flow| {
+            let mut last_frame = Instant::now();
+            let mut lost_frames = 0;
+            let mut minimized = false;
+            let mut cursor = (0.0, 0.0);
+            let mut redraw = true;
+
+            event_loop.run(move |event, event_loop, control_flow| {
                 // control_flow.set_poll();
 
                 match event {
                     Event::MainEventsCleared => {
-                        window.request_redraw();
+                        redraw |= graphics.app.take_redraw();
+
+                        let policy = self.screens.iter()
+                            .map(|screen| screen.redraw_policy())
+                            .reduce(RedrawPolicy::max)
+                            .unwrap_or(RedrawPolicy::Continuous);
+
+                        let frame_cap = match policy {
+                            RedrawPolicy::Capped(fps) => Some(config.frame_cap.map_or(fps, |cap| cap.min(fps))),
+                            _ => config.frame_cap,
+                        };
+
+                        match frame_cap {
+                            _ if minimized => control_flow.set_wait(),
+                            _ if policy == RedrawPolicy::OnDemand && !redraw => control_flow.set_wait(),
+
+                            Some(cap) if cap > 0 => {
+                                let next_frame = last_frame + Duration::from_secs_f64(1.0 / cap as f64);
+                                if Instant::now() >= next_frame {
+                                    control_flow.set_poll();
+                                    window.request_redraw();
+                                } else {
+                                    control_flow.set_wait_until(next_frame);
+                                }
+                            }
+
+                            _ => {
+                                control_flow.set_poll();
+                                window.request_redraw();
+                            }
+                        }
                     }
 
                     Event::RedrawRequested(window_id) if window_id == window.id() => {
-                        // let now = Instant::now();
-                        if let Ok(surface_texture) = graphics.surface.get_current_texture() {
-                            let view = surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
+                        redraw = false;
+
+                        let now = Instant::now();
+                        let delta = now.duration_since(last_frame);
+                        last_frame = now;
+
+                        // Picks up edited shaders before anything draws with them
+                        graphics.shaders.poll(&graphics);
+
+                        let update_start = Instant::now();
+                        for screen in &mut self.screens {
+                            screen.update(&mut state, delta, &mut graphics);
+                        }
+
+                        let update = update_start.elapsed();
+                        let surface_texture = match graphics.surface.as_ref() {
+                            Some(surface) => surface.get_current_texture(),
+                            None => return,
+                        };
+
+                        match surface_texture {
+                            Ok(surface_texture) => {
+                                lost_frames = 0;
+                                let view = surface_texture.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+                                // Screenshots render into a texture that can be read back, which is then copied into the frame
+                                let screenshot = graphics.take_screenshot().map(|path| (path, graphics.make_screenshot_target()));
+                                let target = screenshot.as_ref().map_or(&view, |(_, texture)| &texture.view);
                 
-                            for screen in &mut self.screens {
-                                screen.render(&mut state, &view, &mut graphics);
-                            }
+                                let render_start = Instant::now();
+                                graphics.clear_frame(target);
+                                for screen in &mut self.screens {
+                                    screen.render(&mut state, target, &mut graphics);
+                                }
+
+                                if let Some((_, texture)) = &screenshot {
+                                    graphics.blit(texture, &view);
+                                }
+
+                                let render = render_start.elapsed();
+                                let submit = timing::take_submit();
                 
-                            surface_texture.present();
-                        }
-                    }
+                                let present_start = Instant::now();
+                                surface_texture.present();
 
-                    Event::WindowEvent { event, window_id } if window_id == window.id() => {
-                        match event {
-                            WindowEvent::CursorMoved { device_id, position, .. } => {
+                                graphics.frame_stats.push(FrameTimings {
+                                    update,
+                                    encode  : render.saturating_sub(submit),
+                                    submit,
+                                    present : present_start.elapsed(),
+                                    total   : delta,
+                                });
+
+                                if let Some((path, texture)) = screenshot {
+                                    match graphics.capture(&texture.texture).and_then(|image| capture::save(&image, &path)) {
+                                        Ok(()) => info!("Saved screenshot to {}", path.display()),
+                                        Err(e) => error!("Failed to save screenshot: {e}"),
+                                    }
+                                }
                             }
 
-                            WindowEvent::MouseInput { device_id, state: button_state, button, .. } => {
+                            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                                lost_frames += 1;
+                                if lost_frames <= MAX_LOST_FRAMES {
+                                    graphics.reconfigure();
+                                } else {
+                                    // Reconfiguring did not help, assume the device is gone
+                                    warn!("Surface lost, recreating graphics context");
+                                    if pollster::block_on(graphics.recreate(&window, &config)) {
+                                        lost_frames = 0;
+                                        for screen in &mut self.screens {
+                                            screen.recreate(&mut state, &mut graphics);
+                                        }
+                                    } else {
+                                        error!("Failed to recreate graphics context");
+                                        control_flow.set_exit_with_code(1);
+                                    }
+                                }
+
+                                // Nothing was drawn, on demand screens would otherwise wait for the next event
+                                redraw = true;
+                                window.request_redraw();
                             }
 
-                            WindowEvent::KeyboardInput { device_id, input, is_synthetic } => {
+                            Err(wgpu::SurfaceError::OutOfMemory) => {
+                                error!("Out of memory, exiting");
+                                control_flow.set_exit_with_code(1);
                             }
 
-                            WindowEvent::ModifiersChanged(modifiers) => {
+                            Err(wgpu::SurfaceError::Timeout) => {
+                                warn!("Surface timed out, skipping frame");
                             }
+                        }
+                    }
+
+                    Event::WindowEvent { event, window_id } if window_id == window.id() => {
+                        // Anything that reaches the window may change what's on screen
+                        redraw = true;
 
+                        if let Some(input) = Input::from_window_event(&event, graphics.scale_factor, cursor) {
+                            if let Input::CursorMoved { x, y } = input {
+                                cursor = (x, y);
+                            }
+
+                            for screen in &mut self.screens {
+                                screen.input(&mut state, &input, &mut graphics);
+                            }
+                        }
+
+                        match event {
                             WindowEvent::Resized(PhysicalSize::<u32> { width, height }) => {
+                                // Minimized windows report a zero size
+                                let is_minimized = width == 0 || height == 0;
+                                if is_minimized != minimized {
+                                    minimized = is_minimized;
+                                    for screen in &mut self.screens {
+                                        screen.minimize(&mut state, minimized, &mut graphics);
+                                    }
+                                }
+
                                 if width > 0 && height > 0 {
-                                    graphics.surface_configuration.width = width as u32;
-                                    graphics.surface_configuration.height = height as u32;
-                                    graphics.surface.configure(&graphics.device, &graphics.surface_configuration);
+                                    graphics.resize(width, height);
                         
                                     for screen in &mut self.screens {
                                         screen.resize(&mut state, width, height, &mut graphics);
@@ -95,19 +241,72 @@ impl<State: 'static> App<State> {
                                 
                             }
 
-                            WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size  } => {
+                            WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+                                let PhysicalSize::<u32> { width, height } = *new_inner_size;
+                                graphics.scale_factor = scale_factor;
+                                graphics.resize(width, height);
+
+                                for screen in &mut self.screens {
+                                    screen.rescale(&mut state, scale_factor, &mut graphics);
+                                }
+
+                                if width > 0 && height > 0 {
+                                    for screen in &mut self.screens {
+                                        screen.resize(&mut state, width, height, &mut graphics);
+                                    }
+                                }
+                            }
+
+                            WindowEvent::Focused(focused) => {
+                                for screen in &mut self.screens {
+                                    screen.focus(&mut state, focused, &mut graphics);
+                                }
                             }
 
                             WindowEvent::CloseRequested => {
-                                *control_flow = ControlFlow::Exit;
+                                // Every screen is asked, so each one gets a chance to react
+                                let mut close = true;
+                                for screen in &mut self.screens {
+                                    close &= screen.close_requested(&mut state, &mut graphics);
+                                }
+
+                                if close {
+                                    *control_flow = ControlFlow::Exit;
+                                }
                             }
 
                             _ => {}
                         }
                     }
 
+                    Event::LoopDestroyed => {
+                        for screen in &mut self.screens {
+                            screen.exit(&mut state, &mut graphics);
+                        }
+                    }
+
                     _ => {}
                 }
+
+                for command in graphics.app.take() {
+                    match command {
+                        Command::Exit => control_flow.set_exit(),
+                        Command::SetTitle(title) => window.set_title(&title),
+                        Command::SetFullscreen(fullscreen) => {
+                            window.set_fullscreen(fullscreen.to_winit(window.current_monitor()));
+                        }
+
+                        Command::ToggleFullscreen => {
+                            let fullscreen = match (window.fullscreen(), config.fullscreen) {
+                                (Some(_), _)                 => Fullscreen::Windowed,
+                                (None, Fullscreen::Windowed) => Fullscreen::Borderless,
+                                (None, fullscreen)           => fullscreen,
+                            };
+
+                            window.set_fullscreen(fullscreen.to_winit(window.current_monitor().or_else(|| event_loop.primary_monitor())));
+                        }
+                    }
+                }
             });
         });
     }
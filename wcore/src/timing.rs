@@ -0,0 +1,129 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+use instant::Duration;
+
+thread_local! {
+    static SUBMIT_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// Adds to the time spent in `Queue::submit` during the current frame.
+pub fn record_submit(duration: Duration) {
+    SUBMIT_TIME.with(|time| time.set(time.get() + duration));
+}
+
+/// Returns and resets the time spent in `Queue::submit` since the last call.
+pub fn take_submit() -> Duration {
+    return SUBMIT_TIME.with(|time| time.replace(Duration::ZERO));
+}
+
+/// CPU time spent in each phase of a single frame.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameTimings {
+    pub update  : Duration,
+    pub encode  : Duration,
+    pub submit  : Duration,
+    pub present : Duration,
+
+    /// Time since the previous frame started.
+    pub total   : Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Update,
+    Encode,
+    Submit,
+    Present,
+    Total,
+}
+
+impl Phase {
+    pub fn of(self, timings: &FrameTimings) -> Duration {
+        return match self {
+            Phase::Update  => timings.update,
+            Phase::Encode  => timings.encode,
+            Phase::Submit  => timings.submit,
+            Phase::Present => timings.present,
+            Phase::Total   => timings.total,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Summary {
+    pub avg : Duration,
+    pub p99 : Duration,
+    pub max : Duration,
+}
+
+/// Rolling window over the last frames.
+pub struct FrameStats {
+    history  : VecDeque<FrameTimings>,
+    capacity : usize,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        return Self::new(240);
+    }
+}
+
+impl FrameStats {
+    pub fn new(capacity: usize) -> Self {
+        return Self {
+            history  : VecDeque::with_capacity(capacity),
+            capacity : capacity.max(1),
+        };
+    }
+
+    pub fn push(&mut self, timings: FrameTimings) {
+        if self.history.len() == self.capacity {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(timings);
+    }
+
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    /// Oldest frame first.
+    pub fn history(&self) -> impl Iterator<Item = &FrameTimings> {
+        return self.history.iter();
+    }
+
+    pub fn last(&self) -> Option<&FrameTimings> {
+        return self.history.back();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.history.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.history.is_empty();
+    }
+
+    pub fn capacity(&self) -> usize {
+        return self.capacity;
+    }
+
+    pub fn summary(&self, phase: Phase) -> Summary {
+        if self.history.is_empty() {
+            return Summary::default();
+        }
+
+        let mut samples = self.history.iter().map(|timings| phase.of(timings)).collect::<Vec<_>>();
+        samples.sort_unstable();
+
+        let sum: Duration = samples.iter().sum();
+        let p99 = ((samples.len() as f64 * 0.99).ceil() as usize).clamp(1, samples.len()) - 1;
+        return Summary {
+            avg : sum / samples.len() as u32,
+            p99 : samples[p99],
+            max : samples[samples.len() - 1],
+        };
+    }
+}